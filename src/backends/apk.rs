@@ -0,0 +1,362 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::ErrorKind::NotFound;
+use std::io::Write;
+use std::path::Path;
+
+use color_eyre::Result;
+use color_eyre::eyre::{Context, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::cmd::{run_command, run_command_for_stdout};
+use crate::prelude::*;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
+pub struct Apk;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ApkConfig {}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApkPackageOptions {
+    /// The tag of a pinned repository to install the package from, such as `edge`.
+    pub tag: Option<String>,
+    /// A version constraint including its operator, such as `=1.2.3-r0` or `~1.2`.
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApkRepoOptions {
+    /// The tag used to pin packages to this repository, such as `edge`.
+    pub tag: Option<String>,
+}
+
+/// The file containing the explicitly installed packages (and their constraints).
+const APK_WORLD_FILE: &str = "/etc/apk/world";
+/// The file containing the repositories, one per line.
+const APK_REPOSITORIES_FILE: &str = "/etc/apk/repositories";
+
+impl Backend for Apk {
+    type Config = ApkConfig;
+    type PackageOptions = ApkPackageOptions;
+    type RepoOptions = ApkRepoOptions;
+
+    fn invalid_package_help_text() -> String {
+        String::new()
+    }
+
+    fn is_valid_package_name(_: &str) -> Option<bool> {
+        None
+    }
+
+    fn get_all_packages(_: &Self::Config) -> Result<BTreeSet<String>> {
+        let stdout =
+            run_command_for_stdout(["apk", "list", "--available"], Perms::Same, StdErr::Hide)?;
+
+        // example output:
+        // busybox-1.36.1-r29 x86_64 {busybox} (GPL-2.0-only) [installed]
+        Ok(stdout
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .filter_map(|x| x.rsplitn(3, '-').nth(2))
+            .map(String::from)
+            .collect())
+    }
+
+    fn get_installed_packages(
+        config: &Self::Config,
+    ) -> Result<BTreeMap<String, Self::PackageOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let world = match std::fs::read_to_string(APK_WORLD_FILE) {
+            Ok(world) => world,
+            Err(err) if err.kind() == NotFound => return Ok(BTreeMap::new()),
+            Err(err) => return Err(err).wrap_err(eyre!("reading {APK_WORLD_FILE:?}")),
+        };
+
+        Ok(world
+            .split_whitespace()
+            // conflicts such as `!package` prevent a package from being installed
+            .filter(|x| !x.starts_with('!'))
+            .map(parse_world_entry)
+            .collect())
+    }
+
+    fn install_packages(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        no_confirm: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        if !packages.is_empty() {
+            run_command(
+                ["apk", "add"]
+                    .into_iter()
+                    .chain((!no_confirm).then_some("--interactive"))
+                    .map(String::from)
+                    .chain(
+                        packages
+                            .iter()
+                            .map(|(package, options)| world_entry(package, options)),
+                    ),
+                Perms::Sudo,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn uninstall_packages(
+        packages: &BTreeSet<String>,
+        no_confirm: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        if !packages.is_empty() {
+            run_command(
+                ["apk", "del"]
+                    .into_iter()
+                    .chain((!no_confirm).then_some("--interactive"))
+                    .chain(packages.iter().map(String::as_str)),
+                Perms::Sudo,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_packages(
+        packages: &BTreeSet<String>,
+        no_confirm: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        if !packages.is_empty() {
+            run_command(
+                ["apk", "upgrade"]
+                    .into_iter()
+                    .chain((!no_confirm).then_some("--interactive"))
+                    .chain(packages.iter().map(String::as_str)),
+                Perms::Sudo,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_all_packages(no_confirm: bool, _: &Self::Config) -> Result<()> {
+        run_command(
+            ["apk", "upgrade"]
+                .into_iter()
+                .chain((!no_confirm).then_some("--interactive")),
+            Perms::Sudo,
+        )
+    }
+
+    fn clean_cache(config: &Self::Config) -> Result<()> {
+        // `apk cache clean` fails if the package cache hasn't been enabled
+        if Self::version(config).is_err() || !Path::new("/etc/apk/cache").exists() {
+            return Ok(());
+        }
+
+        run_command(["apk", "cache", "clean"], Perms::Sudo)
+    }
+
+    fn refresh(_: &Self::Config) -> Result<()> {
+        run_command(["apk", "update"], Perms::Sudo)
+    }
+
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        Ok(read_repositories()?
+            .iter()
+            .filter_map(|line| parse_repository_line(line))
+            .collect())
+    }
+
+    fn add_repos(
+        repos: &BTreeMap<String, Self::RepoOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        if repos.is_empty() {
+            return Ok(());
+        }
+
+        let mut lines = read_repositories()?;
+
+        for (repo, options) in repos {
+            lines.push(match &options.tag {
+                Some(tag) => format!("@{tag} {repo}"),
+                None => repo.clone(),
+            });
+        }
+
+        write_repositories(&lines)?;
+
+        run_command(["apk", "update"], Perms::Sudo)
+    }
+
+    fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        if repos.is_empty() {
+            return Ok(());
+        }
+
+        let lines = read_repositories()?
+            .into_iter()
+            .filter(|line| parse_repository_line(line).is_none_or(|(x, _)| !repos.contains(&x)))
+            .collect::<Vec<_>>();
+
+        write_repositories(&lines)
+    }
+
+    fn version(_: &Self::Config) -> Result<String> {
+        run_command_for_stdout(["apk", "--version"], Perms::Same, StdErr::Show)
+    }
+}
+
+/// Parses a world entry such as `package@tag=1.2.3-r0` into its name and options.
+fn parse_world_entry(entry: &str) -> (String, ApkPackageOptions) {
+    let (name_and_tag, version) = match entry.find(['=', '<', '>', '~']) {
+        Some(index) => (&entry[..index], Some(entry[index..].to_string())),
+        None => (entry, None),
+    };
+
+    let (name, tag) = match name_and_tag.split_once('@') {
+        Some((name, tag)) => (name, Some(tag.to_string())),
+        None => (name_and_tag, None),
+    };
+
+    (name.to_string(), ApkPackageOptions { tag, version })
+}
+
+fn world_entry(package: &str, options: &ApkPackageOptions) -> String {
+    format!(
+        "{package}{}{}",
+        options
+            .tag
+            .as_ref()
+            .map(|x| format!("@{x}"))
+            .unwrap_or_default(),
+        options.version.as_deref().unwrap_or_default()
+    )
+}
+
+/// Parses a repositories line such as `@edge https://dl-cdn.alpinelinux.org/alpine/edge/main`
+/// into its url and options, ignoring comments and blank lines.
+fn parse_repository_line(line: &str) -> Option<(String, ApkRepoOptions)> {
+    let line = line.trim();
+
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let mut parts = line.split_whitespace();
+    let first = parts.next()?;
+
+    match first.strip_prefix('@') {
+        Some(tag) => Some((
+            parts.next()?.to_string(),
+            ApkRepoOptions {
+                tag: Some(tag.to_string()),
+            },
+        )),
+        None => Some((first.to_string(), ApkRepoOptions { tag: None })),
+    }
+}
+
+fn read_repositories() -> Result<Vec<String>> {
+    match std::fs::read_to_string(APK_REPOSITORIES_FILE) {
+        Ok(contents) => Ok(contents.lines().map(String::from).collect()),
+        Err(err) if err.kind() == NotFound => Ok(Vec::new()),
+        Err(err) => Err(err).wrap_err(eyre!("reading {APK_REPOSITORIES_FILE:?}")),
+    }
+}
+
+fn write_repositories(lines: &[String]) -> Result<()> {
+    let mut repositories = tempfile::NamedTempFile::new()?;
+    for line in lines {
+        writeln!(repositories, "{line}")?;
+    }
+
+    run_command(
+        [
+            "install",
+            "-D",
+            "-m",
+            "644",
+            repositories
+                .path()
+                .to_str()
+                .ok_or(eyre!("temporary file path should be valid utf-8"))?,
+            APK_REPOSITORIES_FILE,
+        ],
+        Perms::Sudo,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn world_entries() {
+        let entry = |tag: Option<&str>, version: Option<&str>| ApkPackageOptions {
+            tag: tag.map(String::from),
+            version: version.map(String::from),
+        };
+
+        assert_eq!(
+            parse_world_entry("busybox"),
+            ("busybox".to_string(), entry(None, None))
+        );
+        assert_eq!(
+            parse_world_entry("pkg=1.2"),
+            ("pkg".to_string(), entry(None, Some("=1.2")))
+        );
+        assert_eq!(
+            parse_world_entry("pkg@edge"),
+            ("pkg".to_string(), entry(Some("edge"), None))
+        );
+        assert_eq!(
+            parse_world_entry("pkg@edge~1.2"),
+            ("pkg".to_string(), entry(Some("edge"), Some("~1.2")))
+        );
+
+        // entries are written back unchanged
+        for line in ["busybox", "pkg=1.2", "pkg@edge", "pkg@edge>=1.2.3-r0"] {
+            let (package, options) = parse_world_entry(line);
+            assert_eq!(world_entry(&package, &options), line);
+        }
+    }
+
+    #[test]
+    fn repository_lines() {
+        assert_eq!(
+            parse_repository_line("https://dl-cdn.alpinelinux.org/alpine/v3.20/main"),
+            Some((
+                "https://dl-cdn.alpinelinux.org/alpine/v3.20/main".to_string(),
+                ApkRepoOptions { tag: None }
+            ))
+        );
+        assert_eq!(
+            parse_repository_line("@edge https://dl-cdn.alpinelinux.org/alpine/edge/main"),
+            Some((
+                "https://dl-cdn.alpinelinux.org/alpine/edge/main".to_string(),
+                ApkRepoOptions {
+                    tag: Some("edge".to_string())
+                }
+            ))
+        );
+        assert_eq!(
+            parse_repository_line("#https://dl-cdn.alpinelinux.org/alpine/v3.20/community"),
+            None
+        );
+        assert_eq!(parse_repository_line("  # a comment"), None);
+        assert_eq!(parse_repository_line(""), None);
+    }
+}
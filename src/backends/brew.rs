@@ -6,6 +6,7 @@ use color_eyre::Result;
 use color_eyre::eyre::eyre;
 use serde::{Deserialize, Serialize};
 use serde_inline_default::serde_inline_default;
+use serde_json::Value;
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
 pub struct Brew;
@@ -17,7 +18,10 @@ pub struct BrewConfig {}
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct BrewPackageOptions {}
+pub struct BrewPackageOptions {
+    #[serde(default)]
+    pub args: Vec<String>,
+}
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -47,23 +51,13 @@ impl Backend for Brew {
             return Ok(BTreeMap::new());
         }
 
-        let formulae = run_command_for_stdout(
-            ["brew", "list", "-1", "--quiet", "--installed-on-request"],
-            Perms::Same,
-            StdErr::Show,
-        )?;
-
-        let casks = run_command_for_stdout(
-            ["brew", "list", "-1", "--cask", "--quiet"],
+        let stdout = run_command_for_stdout(
+            ["brew", "info", "--json=v2", "--installed"],
             Perms::Same,
             StdErr::Show,
         )?;
 
-        Ok(formulae
-            .lines()
-            .chain(casks.lines())
-            .map(|x| (x.to_string(), Self::PackageOptions {}))
-            .collect())
+        extract_packages(&stdout)
     }
 
     fn install_packages(
@@ -71,19 +65,25 @@ impl Backend for Brew {
         _: bool,
         _: &Self::Config,
     ) -> Result<()> {
-        for package in packages.keys() {
-            run_command(["brew", "install", package.as_str()], Perms::Same)?;
+        for (package, options) in packages {
+            let (kind, name) = split_kind(package);
+
+            run_command(
+                ["brew", "install", kind]
+                    .into_iter()
+                    .chain(options.args.iter().map(String::as_str))
+                    .chain([name]),
+                Perms::Same,
+            )?;
         }
 
         Ok(())
     }
 
     fn uninstall_packages(packages: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
-        if !packages.is_empty() {
+        for (kind, names) in group_by_kind(packages) {
             run_command(
-                ["brew", "remove"]
-                    .into_iter()
-                    .chain(packages.iter().map(String::as_str)),
+                ["brew", "remove", kind].into_iter().chain(names),
                 Perms::Same,
             )?;
         }
@@ -92,11 +92,9 @@ impl Backend for Brew {
     }
 
     fn update_packages(packages: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
-        if !packages.is_empty() {
+        for (kind, names) in group_by_kind(packages) {
             run_command(
-                ["brew", "upgrade"]
-                    .into_iter()
-                    .chain(packages.iter().map(String::as_str)),
+                ["brew", "upgrade", kind].into_iter().chain(names),
                 Perms::Same,
             )?;
         }
@@ -157,3 +155,162 @@ impl Backend for Brew {
         run_command_for_stdout(["brew", "--version"], Perms::Same, StdErr::Show)
     }
 }
+
+/// Casks are prefixed with `cask:` so that they don't conflict with formulae of the same name.
+pub const CASK_PREFIX: &str = "cask:";
+
+/// Returns the `--formula` or `--cask` option for the package along with its name without the
+/// `cask:` prefix.
+fn split_kind(package: &str) -> (&'static str, &str) {
+    match package.strip_prefix(CASK_PREFIX) {
+        Some(name) => ("--cask", name),
+        None => ("--formula", package),
+    }
+}
+
+fn group_by_kind(packages: &BTreeSet<String>) -> BTreeMap<&'static str, Vec<&str>> {
+    let mut groups: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for package in packages {
+        let (kind, name) = split_kind(package);
+        groups.entry(kind).or_default().push(name);
+    }
+    groups
+}
+
+fn extract_packages(stdout: &str) -> Result<BTreeMap<String, BrewPackageOptions>> {
+    let value: Value = serde_json::from_str(stdout)?;
+
+    let mut packages = BTreeMap::new();
+
+    for formula in value["formulae"]
+        .as_array()
+        .ok_or(eyre!("the formulae value should be an array"))?
+    {
+        let name = formula["name"]
+            .as_str()
+            .ok_or(eyre!("formula name should be a string"))?;
+
+        // implicitly installed dependencies are not explicit packages
+        let Some(installed) = formula["installed"]
+            .as_array()
+            .ok_or(eyre!("formula installed value should be an array"))?
+            .iter()
+            .find(|x| x["installed_on_request"].as_bool() == Some(true))
+        else {
+            continue;
+        };
+
+        let head = installed["version"]
+            .as_str()
+            .is_some_and(|x| x.starts_with("HEAD"));
+
+        let args = head
+            .then(|| "--HEAD".to_string())
+            .into_iter()
+            .chain(
+                installed["used_options"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .map(String::from),
+            )
+            .collect();
+
+        packages.insert(name.to_string(), BrewPackageOptions { args });
+    }
+
+    for cask in value["casks"]
+        .as_array()
+        .ok_or(eyre!("the casks value should be an array"))?
+    {
+        let token = cask["token"]
+            .as_str()
+            .ok_or(eyre!("cask token should be a string"))?;
+
+        packages.insert(
+            format!("{CASK_PREFIX}{token}"),
+            BrewPackageOptions::default(),
+        );
+    }
+
+    Ok(packages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_formulae_and_casks() {
+        // trimmed output of `brew info --json=v2 --installed`
+        let stdout = r#"{
+            "formulae": [
+                {
+                    "name": "neovim",
+                    "installed": [
+                        {
+                            "version": "HEAD-4f4a3b2",
+                            "used_options": [],
+                            "installed_on_request": true
+                        }
+                    ]
+                },
+                {
+                    "name": "ffmpeg",
+                    "installed": [
+                        {
+                            "version": "7.0.1",
+                            "used_options": ["--with-fdk-aac"],
+                            "installed_on_request": true
+                        }
+                    ]
+                },
+                {
+                    "name": "firefox",
+                    "installed": [
+                        {
+                            "version": "0.1.0",
+                            "used_options": [],
+                            "installed_on_request": true
+                        }
+                    ]
+                },
+                {
+                    "name": "libuv",
+                    "installed": [
+                        {
+                            "version": "1.48.0",
+                            "used_options": [],
+                            "installed_on_request": false
+                        }
+                    ]
+                }
+            ],
+            "casks": [
+                { "token": "firefox", "installed": "127.0" }
+            ]
+        }"#;
+
+        assert_eq!(
+            extract_packages(stdout).unwrap(),
+            BTreeMap::from([
+                ("cask:firefox".to_string(), BrewPackageOptions::default()),
+                (
+                    "ffmpeg".to_string(),
+                    BrewPackageOptions {
+                        args: vec!["--with-fdk-aac".to_string()],
+                    }
+                ),
+                // a formula with the same name as a cask doesn't replace it
+                ("firefox".to_string(), BrewPackageOptions::default()),
+                (
+                    "neovim".to_string(),
+                    BrewPackageOptions {
+                        args: vec!["--HEAD".to_string()],
+                    }
+                ),
+            ])
+        );
+    }
+}
@@ -1,10 +1,17 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use crate::backends::node::{
+    ScopeConfigFormat, add_scope_registries, extract_dependencies, installed_version, package_spec,
+    read_scope_registries, remove_scope_registries, scope_registries,
+};
 
 use crate::cmd::{run_command, run_command_for_stdout};
 use crate::prelude::*;
 use color_eyre::Result;
 use color_eyre::eyre::eyre;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
 pub struct Bun;
@@ -15,11 +22,15 @@ pub struct BunConfig {}
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct BunPackageOptions {}
+pub struct BunPackageOptions {
+    pub version: Option<String>,
+}
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct BunRepoOptions {}
+pub struct BunRepoOptions {
+    pub registry: Option<String>,
+}
 
 impl Backend for Bun {
     type Config = BunConfig;
@@ -45,49 +56,26 @@ impl Backend for Bun {
             return Ok(BTreeMap::new());
         }
 
-        let Ok(output) =
-            run_command_for_stdout(["bun", "pm", "ls", "--global"], Perms::Same, StdErr::Hide)
-        else {
-            //unfortunately when there are no global packages installed bun returns an error rather
-            //than saying zero packages
-            return Ok(BTreeMap::new());
-        };
-
-        let lines = output.lines().collect::<Vec<_>>();
-
-        // example output:
-        // /home/ripytide/.bun/install/global node_modules (292)
-        // ├── @rspack/cli@1.4.10
-        // └── glob@11.0.3
+        //`bun pm ls --global` has no json output so we read the global `package.json` file instead
+        let global_dir = global_dir()?;
+        let package_file = global_dir.join("package.json");
 
-        let mut packages = BTreeMap::new();
-        for line in &lines[1..] {
-            let tree_parts = line.split_whitespace().collect::<Vec<_>>();
-            if tree_parts.len() != 2 {
-                return Err(eyre!("unexpected tree parts"));
-            }
-
-            let name = if tree_parts[1].starts_with('@') {
-                let package_parts = tree_parts[1].split('@').collect::<Vec<_>>();
-
-                if package_parts.len() != 3 {
-                    return Err(eyre!("unexpected package parts"));
-                }
-
-                format!("@{}", package_parts[1])
-            } else {
-                let package_parts = tree_parts[1].split('@').collect::<Vec<_>>();
-                if package_parts.len() != 2 {
-                    return Err(eyre!("unexpected package parts"));
-                }
+        if !package_file.exists() {
+            return Ok(BTreeMap::new());
+        }
 
-                package_parts[0].to_string()
-            };
+        let value: Value = serde_json::from_str(&std::fs::read_to_string(package_file)?)?;
 
-            packages.insert(name, BunPackageOptions {});
-        }
+        let node_modules = global_dir.join("node_modules");
 
-        Ok(packages)
+        // the package file only contains the version ranges so we prefer the installed versions
+        Ok(extract_dependencies(&value)?
+            .into_iter()
+            .map(|(name, version)| {
+                let version = installed_version(&node_modules, &name).or(version);
+                (name, BunPackageOptions { version })
+            })
+            .collect())
     }
 
     fn install_packages(
@@ -99,7 +87,12 @@ impl Backend for Bun {
             run_command(
                 ["bun", "install", "--global"]
                     .into_iter()
-                    .chain(packages.keys().map(String::as_str)),
+                    .map(String::from)
+                    .chain(
+                        packages.iter().map(|(package, options)| {
+                            package_spec(package, options.version.as_ref())
+                        }),
+                    ),
                 Perms::Same,
             )?;
         }
@@ -143,8 +136,24 @@ impl Backend for Bun {
         })
     }
 
-    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
-        Ok(BTreeMap::new())
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        Ok(
+            read_scope_registries(&scope_config_path()?, ScopeConfigFormat::Bunfig)?
+                .into_iter()
+                .map(|(scope, registry)| {
+                    (
+                        scope,
+                        Self::RepoOptions {
+                            registry: Some(registry),
+                        },
+                    )
+                })
+                .collect(),
+        )
     }
 
     fn add_repos(
@@ -153,21 +162,51 @@ impl Backend for Bun {
         _: &Self::Config,
     ) -> Result<()> {
         if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+            return Ok(());
         }
+
+        add_scope_registries(
+            &scope_config_path()?,
+            ScopeConfigFormat::Bunfig,
+            &scope_registries(
+                repos
+                    .iter()
+                    .map(|(scope, options)| (scope, &options.registry)),
+            )?,
+        )
     }
 
     fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
         if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+            return Ok(());
         }
+
+        remove_scope_registries(&scope_config_path()?, ScopeConfigFormat::Bunfig, repos)
     }
 
     fn version(_: &Self::Config) -> Result<String> {
         run_command_for_stdout(["bun", "--version"], Perms::Same, StdErr::Show)
     }
 }
+
+/// Returns the directory bun installs global packages into, which contains their `package.json`.
+fn global_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("BUN_INSTALL_GLOBAL_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+
+    std::env::var_os("BUN_INSTALL")
+        .map(PathBuf::from)
+        .or_else(|| home::home_dir().map(|x| x.join(".bun")))
+        .map(|x| x.join("install/global"))
+        .ok_or(eyre!("getting the home directory"))
+}
+
+/// Returns the path of the global `.bunfig.toml` file.
+fn scope_config_path() -> Result<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(home::home_dir)
+        .map(|x| x.join(".bunfig.toml"))
+        .ok_or(eyre!("getting the home directory"))
+}
@@ -1,9 +1,13 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::io::ErrorKind::NotFound;
+use std::path::{Path, PathBuf};
 
 use color_eyre::Result;
 use color_eyre::eyre::{Context, eyre};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use toml_edit::{DocumentMut, Item, Table, value};
 
 use crate::cmd::{run_command, run_command_for_stdout};
 use crate::prelude::*;
@@ -31,17 +35,30 @@ pub struct CargoPackageOptions {
     all_features: Option<bool>,
     #[serde(default)]
     no_default_features: Option<bool>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     features: Vec<String>,
     #[serde(default)]
     locked: Option<bool>,
     #[serde(default)]
     binstall: Option<bool>,
+    #[serde(default)]
+    registry: Option<String>,
+    #[serde(default)]
+    branch: Option<String>,
+    #[serde(default)]
+    tag: Option<String>,
+    #[serde(default)]
+    rev: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    bins: Vec<String>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct CargoRepoOptions {}
+pub struct CargoRepoOptions {
+    pub index: Option<String>,
+    pub credential_provider: Option<String>,
+}
 
 impl Backend for Cargo {
     type Config = CargoConfig;
@@ -67,15 +84,13 @@ impl Backend for Cargo {
             return Ok(BTreeMap::new());
         }
 
-        let toml_file = home::cargo_home()
-            .wrap_err("getting the cargo home directory")?
-            .join(".crates.toml");
+        let json_file = cargo_home()?.join(".crates2.json");
 
-        match std::fs::read_to_string(&toml_file) {
-            Ok(contents) => extract_packages(&contents),
+        match std::fs::read_to_string(&json_file) {
+            Ok(contents) => extract_packages(&contents, &get_registries()?),
             Err(err) if err.kind() == NotFound => {
                 log::warn!(
-                    "no .crates.toml file found for cargo, assuming no crates installed yet"
+                    "no .crates2.json file found for cargo, assuming no crates installed yet"
                 );
                 Ok(BTreeMap::new())
             }
@@ -103,16 +118,33 @@ impl Backend for Cargo {
                             .unwrap_or(config.locked)
                             .then_some("--locked"),
                     )
-                    .chain(options.git.is_some().then_some("--git"))
-                    .chain(options.git.as_deref())
-                    .chain((options.all_features == Some(true)).then_some("--all_features"))
+                    .map(String::from)
+                    .chain(flag_value("--version", options.version.as_ref()))
+                    .chain(flag_value("--git", options.git.as_ref()))
+                    .chain(flag_value("--branch", options.branch.as_ref()))
+                    .chain(flag_value("--tag", options.tag.as_ref()))
+                    .chain(flag_value("--rev", options.rev.as_ref()))
+                    .chain(flag_value("--registry", options.registry.as_ref()))
+                    .chain(
+                        (options.all_features == Some(true)).then(|| "--all-features".to_string()),
+                    )
                     .chain(
                         (options.no_default_features == Some(true))
-                            .then_some("--no-default-features"),
+                            .then(|| "--no-default-features".to_string()),
+                    )
+                    .chain(flag_value(
+                        "--features",
+                        (!options.features.is_empty())
+                            .then(|| options.features.join(","))
+                            .as_ref(),
+                    ))
+                    .chain(
+                        options
+                            .bins
+                            .iter()
+                            .flat_map(|x| ["--bin".to_string(), x.clone()]),
                     )
-                    .chain((!options.features.is_empty()).then_some("--features"))
-                    .chain(options.features.iter().map(String::as_str))
-                    .chain([package.as_str()]),
+                    .chain([package.clone()]),
                 Perms::Same,
             )?;
         }
@@ -120,6 +152,35 @@ impl Backend for Cargo {
         Ok(())
     }
 
+    fn package_option_changes(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        config: &Self::Config,
+    ) -> Result<Vec<String>> {
+        Ok(get_drifted_packages(packages, config)?
+            .into_keys()
+            .map(|package| format!("reinstall {package}"))
+            .collect())
+    }
+
+    fn sync_package_options(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        no_confirm: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        let drifted = get_drifted_packages(packages, config)?;
+
+        if !drifted.is_empty() {
+            log::info!(
+                "reinstalling cargo packages whose options have drifted: {:?}",
+                drifted.keys()
+            );
+
+            Self::install_packages(&drifted, no_confirm, config)?;
+        }
+
+        Ok(())
+    }
+
     fn uninstall_packages(packages: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
         if !packages.is_empty() {
             run_command(
@@ -160,6 +221,8 @@ impl Backend for Cargo {
 
         for options in install_options.values_mut() {
             options.locked = Some(config.locked);
+            // the installed version is always read back, so it must be cleared to update
+            options.version = None;
         }
 
         Self::install_packages(&install_options, no_confirm, config)
@@ -169,7 +232,11 @@ impl Backend for Cargo {
         // upstream issue in case cargo ever implements a simpler way to do this
         // https://github.com/rust-lang/cargo/issues/9527
 
-        let install_options = Self::get_installed_packages(config)?;
+        let mut install_options = Self::get_installed_packages(config)?;
+
+        for options in install_options.values_mut() {
+            options.version = None;
+        }
 
         Self::install_packages(&install_options, no_confirm, config)
     }
@@ -181,8 +248,12 @@ impl Backend for Cargo {
             })
     }
 
-    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
-        Ok(BTreeMap::new())
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        Ok(extract_registries(&read_config(&config_path()?)?))
     }
 
     fn add_repos(
@@ -191,18 +262,57 @@ impl Backend for Cargo {
         _: &Self::Config,
     ) -> Result<()> {
         if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+            return Ok(());
         }
+
+        let path = config_path()?;
+        let mut document = read_config(&path)?;
+
+        let registries = document
+            .entry("registries")
+            .or_insert_with(|| {
+                let mut table = Table::new();
+                table.set_implicit(true);
+                Item::Table(table)
+            })
+            .as_table_mut()
+            .ok_or(eyre!("the cargo registries config should be a table"))?;
+
+        for (repo, options) in repos {
+            let index = options
+                .index
+                .as_deref()
+                .ok_or(eyre!("cargo repo {repo:?} is missing the index option"))?;
+
+            let mut registry = Table::new();
+            registry.insert("index", value(index));
+            if let Some(credential_provider) = &options.credential_provider {
+                registry.insert("credential-provider", value(credential_provider));
+            }
+            registries.insert(repo, Item::Table(registry));
+        }
+
+        write_config(&path, &document)
     }
 
     fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
         if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+            return Ok(());
         }
+
+        let path = config_path()?;
+        let mut document = read_config(&path)?;
+
+        if let Some(registries) = document
+            .get_mut("registries")
+            .and_then(Item::as_table_like_mut)
+        {
+            for repo in repos {
+                registries.remove(repo);
+            }
+        }
+
+        write_config(&path, &document)
     }
 
     fn version(_: &Self::Config) -> Result<String> {
@@ -210,51 +320,362 @@ impl Backend for Cargo {
     }
 }
 
-fn extract_packages(contents: &str) -> Result<BTreeMap<String, CargoPackageOptions>> {
-    let toml: toml::Table =
-        toml::from_str(contents).wrap_err("parsing TOML from .crates.toml file")?;
-
-    let v1_section = toml
-        .get("v1")
-        .ok_or(eyre!("missing 'v1' section in .crates.toml"))?
-        .as_table()
-        .ok_or(eyre!("'v1' section should be a table"))?;
+fn flag_value(flag: &str, value: Option<&String>) -> impl Iterator<Item = String> {
+    value
+        .into_iter()
+        .flat_map(move |x| [flag.to_string(), x.clone()])
+}
 
-    let mut packages = BTreeMap::new();
+fn cargo_home() -> Result<PathBuf> {
+    home::cargo_home().wrap_err("getting the cargo home directory")
+}
 
-    for (key, _value) in v1_section {
-        // Key format: "package_name version (source)"
-        // Extract package name (everything before the first space)
-        if let Some((package_name, version_source)) = key.split_once(' ') {
-            let (version, source) = version_source.split_once(' ').unwrap();
-
-            let git = if source.starts_with("(git+") {
-                Some(
-                    source.split('+').collect::<Vec<_>>()[1]
-                        .split('#')
-                        .next()
-                        .unwrap()
-                        .to_string(),
+/// Returns the installed packages whose declared options have drifted, cargo reinstalls a crate by
+/// itself if its version, source or features have changed.
+fn get_drifted_packages(
+    packages: &BTreeMap<String, CargoPackageOptions>,
+    config: &CargoConfig,
+) -> Result<BTreeMap<String, CargoPackageOptions>> {
+    let installed = Cargo::get_installed_packages(config)?;
+
+    Ok(packages
+        .iter()
+        .filter(|(package, options)| {
+            installed.get(*package).is_some_and(|installed| {
+                has_drifted(
+                    options,
+                    installed,
+                    options.binstall.unwrap_or(config.binstall),
                 )
-            } else {
-                None
-            };
-
-            packages.insert(
-                package_name.to_string(),
-                CargoPackageOptions {
-                    version: Some(version.to_string()),
-                    git,
-                    // All of these are not specified
-                    all_features: None,
-                    no_default_features: None,
-                    features: Vec::new(),
-                    locked: None,
-                    binstall: None,
+            })
+        })
+        .map(|(package, options)| (package.clone(), options.clone()))
+        .collect())
+}
+
+/// Returns whether the declared options differ from the installed ones, options that aren't
+/// declared (`None` or an empty list) are ignored.
+///
+/// `locked` and `binstall` aren't recorded by cargo so are never compared, and neither are the
+/// features of binstall packages since `cargo binstall` doesn't record them either.
+fn has_drifted(
+    declared: &CargoPackageOptions,
+    installed: &CargoPackageOptions,
+    binstall: bool,
+) -> bool {
+    let differs = |declared: &Option<String>, installed: &Option<String>| {
+        declared.is_some() && declared != installed
+    };
+
+    let sorted = |x: &[String]| x.iter().cloned().collect::<BTreeSet<_>>();
+
+    let flag_differs = |declared: Option<bool>, installed: Option<bool>| {
+        declared.is_some_and(|x| x != installed.unwrap_or_default())
+    };
+
+    let list_differs = |declared: &[String], installed: &[String]| {
+        !declared.is_empty() && sorted(declared) != sorted(installed)
+    };
+
+    let features_differ = !binstall
+        && (flag_differs(declared.all_features, installed.all_features)
+            || flag_differs(declared.no_default_features, installed.no_default_features)
+            || list_differs(&declared.features, &installed.features));
+
+    declared
+        .version
+        .as_deref()
+        .is_some_and(|x| !version_matches(x, installed.version.as_deref()))
+        || differs(&declared.git, &installed.git)
+        || differs(&declared.branch, &installed.branch)
+        || differs(&declared.tag, &installed.tag)
+        || differs(&declared.rev, &installed.rev)
+        || differs(&declared.registry, &installed.registry)
+        || features_differ
+        || list_differs(&declared.bins, &installed.bins)
+}
+
+/// Returns whether the installed version satisfies the declared `--version`, which like cargo we
+/// treat as an exact version if it is a full version and as a requirement otherwise.
+fn version_matches(declared: &str, installed: Option<&str>) -> bool {
+    let Some(installed) = installed.and_then(|x| semver::Version::parse(x).ok()) else {
+        return false;
+    };
+
+    match semver::Version::parse(declared) {
+        Ok(declared) => declared == installed,
+        Err(_) => semver::VersionReq::parse(declared).is_ok_and(|x| x.matches(&installed)),
+    }
+}
+
+fn config_path() -> Result<PathBuf> {
+    Ok(cargo_home()?.join("config.toml"))
+}
+
+fn read_config(path: &Path) -> Result<DocumentMut> {
+    if !path.is_file() {
+        return Ok(DocumentMut::new());
+    }
+
+    std::fs::read_to_string(path)
+        .wrap_err(eyre!("reading cargo config: {}", path.display()))?
+        .parse()
+        .wrap_err(eyre!("parsing cargo config: {}", path.display()))
+}
+
+fn write_config(path: &Path, document: &DocumentMut) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(path, document.to_string())
+        .wrap_err(eyre!("writing cargo config: {}", path.display()))
+}
+
+/// Returns the alternative registries from the `[registries]` table of a cargo config.
+fn extract_registries(document: &DocumentMut) -> BTreeMap<String, CargoRepoOptions> {
+    document
+        .get("registries")
+        .and_then(Item::as_table_like)
+        .into_iter()
+        .flat_map(|x| x.iter())
+        .filter_map(|(name, registry)| {
+            // entries without an index such as `[registries.crates-io]` only configure the
+            // default registry
+            let index = registry.get("index")?.as_str()?.to_string();
+
+            let credential_provider = registry.get("credential-provider").and_then(|x| {
+                // the credential provider can also be given as an array of arguments
+                x.as_str().map(String::from).or_else(|| {
+                    x.as_array()
+                        .map(|x| x.iter().filter_map(|x| x.as_str()).join(" "))
+                })
+            });
+
+            Some((
+                name.to_string(),
+                CargoRepoOptions {
+                    index: Some(index),
+                    credential_provider,
                 },
-            );
+            ))
+        })
+        .collect()
+}
+
+/// Returns the alternative registries as a map from their index url to their name.
+fn get_registries() -> Result<BTreeMap<String, String>> {
+    Ok(extract_registries(&read_config(&config_path()?)?)
+        .into_iter()
+        .filter_map(|(name, options)| Some((index_url(&options.index?).to_string(), name)))
+        .collect())
+}
+
+/// Strips the protocol prefix from a registry index such as `sparse+https://index.crates.io/`.
+fn index_url(index: &str) -> &str {
+    index
+        .strip_prefix("sparse+")
+        .or_else(|| index.strip_prefix("registry+"))
+        .unwrap_or(index)
+}
+
+fn extract_packages(
+    contents: &str,
+    registries: &BTreeMap<String, String>,
+) -> Result<BTreeMap<String, CargoPackageOptions>> {
+    let json: Value =
+        serde_json::from_str(contents).wrap_err("parsing JSON from .crates2.json file")?;
+
+    let installs = json["installs"]
+        .as_object()
+        .ok_or(eyre!("'installs' in .crates2.json should be an object"))?;
+
+    let strings = |x: &Value| {
+        x.as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+
+    let mut packages = BTreeMap::new();
+
+    for (key, install) in installs {
+        // key format: "package_name version (source)"
+        let mut parts = key.splitn(3, ' ');
+        let (Some(package_name), Some(version), Some(source)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(eyre!("unexpected .crates2.json key: {key:?}"));
+        };
+        let source = source.trim_start_matches('(').trim_end_matches(')');
+
+        let mut options = CargoPackageOptions {
+            version: Some(version.to_string()),
+            all_features: install["all_features"].as_bool().filter(|x| *x),
+            no_default_features: install["no_default_features"].as_bool().filter(|x| *x),
+            features: strings(&install["features"]),
+            bins: strings(&install["bins"]),
+            ..Default::default()
+        };
+
+        if let Some(git) = source.strip_prefix("git+") {
+            // such as "https://github.com/user/repo?branch=main#commit"
+            let url = git.split('#').next().unwrap_or(git);
+            let (url, query) = url.split_once('?').unwrap_or((url, ""));
+
+            options.git = Some(url.to_string());
+            for (key, value) in query.split('&').filter_map(|x| x.split_once('=')) {
+                match key {
+                    "branch" => options.branch = Some(value.to_string()),
+                    "tag" => options.tag = Some(value.to_string()),
+                    "rev" => options.rev = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+        } else if source.starts_with("registry+") || source.starts_with("sparse+") {
+            // crates.io is the default registry so doesn't need to be specified
+            options.registry = registries.get(index_url(source)).cloned();
         }
+
+        packages.insert(package_name.to_string(), options);
     }
 
     Ok(packages)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `.crates2.json` after `cargo install ripgrep --features pcre2`, `cargo install --git
+    // https://github.com/helix-editor/helix --tag 25.07.1 helix-term` and a binstall of cargo-nextest
+    const CRATES2: &str = indoc::indoc! {r#"
+        {
+          "installs": {
+            "cargo-nextest 0.9.100 (registry+https://github.com/rust-lang/crates.io-index)": {
+              "version_req": null,
+              "bins": ["cargo-nextest"],
+              "features": [],
+              "all_features": false,
+              "no_default_features": false,
+              "profile": "release",
+              "target": "x86_64-unknown-linux-gnu",
+              "rustc": ""
+            },
+            "helix-term 25.7.1 (git+https://github.com/helix-editor/helix?tag=25.07.1#a05c151b)": {
+              "version_req": null,
+              "bins": ["hx"],
+              "features": [],
+              "all_features": false,
+              "no_default_features": false,
+              "profile": "release",
+              "target": "x86_64-unknown-linux-gnu",
+              "rustc": "rustc 1.89.0 (29483883e 2025-08-04)\nbinary: rustc\n"
+            },
+            "ripgrep 14.1.1 (sparse+https://cargo.example.com/index/)": {
+              "version_req": null,
+              "bins": ["rg"],
+              "features": ["pcre2"],
+              "all_features": false,
+              "no_default_features": false,
+              "profile": "release",
+              "target": "x86_64-unknown-linux-gnu",
+              "rustc": "rustc 1.89.0 (29483883e 2025-08-04)\nbinary: rustc\n"
+            }
+          }
+        }
+    "#};
+
+    fn strings(x: &[&str]) -> Vec<String> {
+        x.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn extract_installed_packages() {
+        let registries = BTreeMap::from([(
+            "https://cargo.example.com/index/".to_string(),
+            "corp".to_string(),
+        )]);
+
+        assert_eq!(
+            extract_packages(CRATES2, &registries).unwrap(),
+            BTreeMap::from([
+                (
+                    "cargo-nextest".to_string(),
+                    CargoPackageOptions {
+                        version: Some("0.9.100".to_string()),
+                        bins: strings(&["cargo-nextest"]),
+                        ..Default::default()
+                    }
+                ),
+                (
+                    "helix-term".to_string(),
+                    CargoPackageOptions {
+                        version: Some("25.7.1".to_string()),
+                        git: Some("https://github.com/helix-editor/helix".to_string()),
+                        tag: Some("25.07.1".to_string()),
+                        bins: strings(&["hx"]),
+                        ..Default::default()
+                    }
+                ),
+                (
+                    "ripgrep".to_string(),
+                    CargoPackageOptions {
+                        version: Some("14.1.1".to_string()),
+                        registry: Some("corp".to_string()),
+                        features: strings(&["pcre2"]),
+                        bins: strings(&["rg"]),
+                        ..Default::default()
+                    }
+                ),
+            ])
+        );
+        assert!(extract_packages(r#"{"installs": {"ripgrep": {}}}"#, &registries).is_err());
+    }
+
+    #[test]
+    fn drift_ignores_undeclared_options() {
+        let installed = CargoPackageOptions {
+            version: Some("14.1.1".to_string()),
+            features: strings(&["pcre2"]),
+            bins: strings(&["rg"]),
+            ..Default::default()
+        };
+        let declared = |version: &str| CargoPackageOptions {
+            version: Some(version.to_string()),
+            features: strings(&["pcre2"]),
+            ..Default::default()
+        };
+
+        assert!(!has_drifted(&declared("14.1.1"), &installed, false));
+        assert!(!has_drifted(&declared("14"), &installed, false));
+        assert!(!has_drifted(&declared("^14.0"), &installed, false));
+        assert!(has_drifted(&declared("14.1.0"), &installed, false));
+        assert!(has_drifted(&declared("<14"), &installed, false));
+
+        assert!(!has_drifted(
+            &CargoPackageOptions::default(),
+            &installed,
+            false
+        ));
+
+        let no_default_features = CargoPackageOptions {
+            no_default_features: Some(true),
+            features: strings(&["pcre2", "simd-accel"]),
+            ..Default::default()
+        };
+        assert!(has_drifted(&no_default_features, &installed, false));
+
+        // binstall doesn't record features
+        assert!(!has_drifted(&no_default_features, &installed, true));
+
+        let bins = CargoPackageOptions {
+            features: strings(&["pcre2"]),
+            bins: strings(&["rg", "rg-extra"]),
+            ..Default::default()
+        };
+        assert!(has_drifted(&bins, &installed, false));
+    }
+}
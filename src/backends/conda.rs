@@ -0,0 +1,668 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use color_eyre::Result;
+use color_eyre::eyre::{Context, ContextCompat, eyre};
+use serde::{Deserialize, Serialize};
+use toml_edit::{Array, DocumentMut, Item, value};
+
+use crate::cmd::{run_command, run_command_for_stdout};
+use crate::prelude::*;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
+pub struct Conda;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CondaConfig {
+    #[serde(default)]
+    pub package_manager: CondaPackageManager,
+    /// The environment to manage packages in with `conda`, `mamba` or `micromamba`, defaults to
+    /// the active environment. Not used with `pixi` as each package is its own environment.
+    #[serde(default)]
+    pub environment: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CondaPackageManager {
+    #[default]
+    Conda,
+    Mamba,
+    Micromamba,
+    Pixi,
+}
+impl CondaPackageManager {
+    pub fn as_command(self) -> &'static str {
+        match self {
+            Self::Conda => "conda",
+            Self::Mamba => "mamba",
+            Self::Micromamba => "micromamba",
+            Self::Pixi => "pixi",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CondaPackageOptions {
+    /// The channel to install the package from such as `conda-forge`.
+    pub channel: Option<String>,
+    /// The version constraint of the package such as `3.11` or `>=1.26`.
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CondaRepoOptions {}
+
+/// The channel conda uses when no channels have been configured.
+const DEFAULT_CHANNEL: &str = "defaults";
+
+impl Backend for Conda {
+    type Config = CondaConfig;
+    type PackageOptions = CondaPackageOptions;
+    type RepoOptions = CondaRepoOptions;
+
+    fn invalid_package_help_text() -> String {
+        indoc::formatdoc! {"
+            A conda package may be invalid due to one of the following issues:
+                - the package name contains a channel or version constraint, use the `channel`
+                  and `version` options instead
+        "}
+    }
+
+    fn is_valid_package_name(package: &str) -> Option<bool> {
+        if package.contains([':', '=', '<', '>', '!', '~', '[', ' ']) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn get_all_packages(_: &Self::Config) -> Result<BTreeSet<String>> {
+        Err(eyre!("unimplemented"))
+    }
+
+    fn get_installed_packages(
+        config: &Self::Config,
+    ) -> Result<BTreeMap<String, Self::PackageOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        match config.package_manager {
+            CondaPackageManager::Pixi => get_pixi_environments(),
+            package_manager => {
+                let stdout = run_command_for_stdout(
+                    [
+                        package_manager.as_command(),
+                        "env",
+                        "export",
+                        "--from-history",
+                    ]
+                    .into_iter()
+                    .chain(environment_args(config))
+                    .chain(["--json"]),
+                    Perms::Same,
+                    StdErr::Show,
+                )?;
+
+                extract_history_packages(&stdout)
+            }
+        }
+    }
+
+    fn install_packages(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        no_confirm: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        match config.package_manager {
+            CondaPackageManager::Pixi => {
+                // each package is installed into its own environment named after the package
+                for (package, options) in packages {
+                    run_command(
+                        ["pixi", "global", "install"]
+                            .into_iter()
+                            .map(String::from)
+                            .chain(
+                                options
+                                    .channel
+                                    .iter()
+                                    .flat_map(|x| ["--channel".to_string(), x.clone()]),
+                            )
+                            .chain([package_spec(package, options.version.as_deref())]),
+                        Perms::Same,
+                    )?;
+                }
+
+                Ok(())
+            }
+            package_manager => run_command(
+                [package_manager.as_command(), "install"]
+                    .into_iter()
+                    .chain(environment_args(config))
+                    .chain(no_confirm.then_some("--yes"))
+                    .map(String::from)
+                    .chain(packages.iter().map(|(package, options)| {
+                        // the channel is part of the spec so that it's recorded in the history
+                        let spec = package_spec(package, options.version.as_deref());
+                        match &options.channel {
+                            Some(channel) => format!("{channel}::{spec}"),
+                            None => spec,
+                        }
+                    })),
+                Perms::Same,
+            ),
+        }
+    }
+
+    fn uninstall_packages(
+        packages: &BTreeSet<String>,
+        no_confirm: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        match config.package_manager {
+            CondaPackageManager::Pixi => run_command(
+                ["pixi", "global", "uninstall"]
+                    .into_iter()
+                    .chain(packages.iter().map(String::as_str)),
+                Perms::Same,
+            ),
+            package_manager => run_command(
+                [package_manager.as_command(), "remove"]
+                    .into_iter()
+                    .chain(environment_args(config))
+                    .chain(no_confirm.then_some("--yes"))
+                    .chain(packages.iter().map(String::as_str)),
+                Perms::Same,
+            ),
+        }
+    }
+
+    fn update_packages(
+        packages: &BTreeSet<String>,
+        no_confirm: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        match config.package_manager {
+            CondaPackageManager::Pixi => run_command(
+                ["pixi", "global", "update"]
+                    .into_iter()
+                    .chain(packages.iter().map(String::as_str)),
+                Perms::Same,
+            ),
+            package_manager => run_command(
+                [package_manager.as_command(), "update"]
+                    .into_iter()
+                    .chain(environment_args(config))
+                    .chain(no_confirm.then_some("--yes"))
+                    .chain(packages.iter().map(String::as_str)),
+                Perms::Same,
+            ),
+        }
+    }
+
+    fn update_all_packages(no_confirm: bool, config: &Self::Config) -> Result<()> {
+        match config.package_manager {
+            CondaPackageManager::Pixi => run_command(["pixi", "global", "update"], Perms::Same),
+            package_manager => run_command(
+                [package_manager.as_command(), "update", "--all"]
+                    .into_iter()
+                    .chain(environment_args(config))
+                    .chain(no_confirm.then_some("--yes")),
+                Perms::Same,
+            ),
+        }
+    }
+
+    fn clean_cache(config: &Self::Config) -> Result<()> {
+        if Self::version(config).is_err() {
+            return Ok(());
+        }
+
+        match config.package_manager {
+            CondaPackageManager::Pixi => {
+                run_command(["pixi", "clean", "cache", "--yes"], Perms::Same)
+            }
+            package_manager => run_command(
+                [package_manager.as_command(), "clean", "--all", "--yes"],
+                Perms::Same,
+            ),
+        }
+    }
+
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let channels = match config.package_manager {
+            CondaPackageManager::Pixi => extract_pixi_channels(&read_pixi_config()?),
+            CondaPackageManager::Conda => parse_channels(&run_command_for_stdout(
+                ["conda", "config", "--show", "channels"],
+                Perms::Same,
+                StdErr::Show,
+            )?),
+            package_manager => parse_channels(&run_command_for_stdout(
+                [package_manager.as_command(), "config", "list", "channels"],
+                Perms::Same,
+                StdErr::Show,
+            )?),
+        };
+
+        Ok(channels
+            .into_iter()
+            .filter(|x| x != DEFAULT_CHANNEL)
+            .map(|x| (x, Self::RepoOptions {}))
+            .collect())
+    }
+
+    fn add_repos(
+        repos: &BTreeMap<String, Self::RepoOptions>,
+        _: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        if repos.is_empty() {
+            return Ok(());
+        }
+
+        match config.package_manager {
+            CondaPackageManager::Pixi => {
+                let mut document = read_pixi_config()?;
+                let mut channels = extract_pixi_channels(&document);
+                channels.extend(repos.keys().cloned());
+                set_pixi_channels(&mut document, &channels);
+                write_pixi_config(&document)
+            }
+            CondaPackageManager::Conda => {
+                for repo in repos.keys() {
+                    run_command(
+                        ["conda", "config", "--add", "channels", repo.as_str()],
+                        Perms::Same,
+                    )?;
+                }
+
+                Ok(())
+            }
+            package_manager => {
+                for repo in repos.keys() {
+                    run_command(
+                        [
+                            package_manager.as_command(),
+                            "config",
+                            "prepend",
+                            "channels",
+                            repo.as_str(),
+                        ],
+                        Perms::Same,
+                    )?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    fn remove_repos(repos: &BTreeSet<String>, _: bool, config: &Self::Config) -> Result<()> {
+        if repos.is_empty() {
+            return Ok(());
+        }
+
+        match config.package_manager {
+            CondaPackageManager::Pixi => {
+                let mut document = read_pixi_config()?;
+                let channels = extract_pixi_channels(&document)
+                    .into_iter()
+                    .filter(|x| !repos.contains(x))
+                    .collect::<Vec<_>>();
+                set_pixi_channels(&mut document, &channels);
+                write_pixi_config(&document)
+            }
+            CondaPackageManager::Conda => {
+                for repo in repos {
+                    run_command(
+                        ["conda", "config", "--remove", "channels", repo.as_str()],
+                        Perms::Same,
+                    )?;
+                }
+
+                Ok(())
+            }
+            package_manager => {
+                for repo in repos {
+                    run_command(
+                        [
+                            package_manager.as_command(),
+                            "config",
+                            "remove",
+                            "channels",
+                            repo.as_str(),
+                        ],
+                        Perms::Same,
+                    )?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    fn version(config: &Self::Config) -> Result<String> {
+        run_command_for_stdout(
+            [config.package_manager.as_command(), "--version"],
+            Perms::Same,
+            StdErr::Show,
+        )
+    }
+}
+
+fn environment_args(config: &CondaConfig) -> impl Iterator<Item = &str> {
+    config
+        .environment
+        .iter()
+        .flat_map(|x| ["--name", x.as_str()])
+}
+
+/// Returns a match spec such as `numpy=1.26` or `numpy>=1.26` for a package.
+fn package_spec(package: &str, version: Option<&str>) -> String {
+    match version {
+        Some(version) if version.starts_with(|x: char| x.is_ascii_digit()) => {
+            format!("{package}={version}")
+        }
+        Some(version) => format!("{package}{version}"),
+        None => package.to_string(),
+    }
+}
+
+/// Parses a match spec such as `conda-forge::numpy=1.26` back into its package name and
+/// options.
+fn parse_package_spec(spec: &str) -> (String, CondaPackageOptions) {
+    let (channel, spec) = match spec.split_once("::") {
+        Some((channel, spec)) => (Some(channel.to_string()), spec),
+        None => (None, spec),
+    };
+
+    let (name, version) = match spec.find(['=', '<', '>', '!', '~', '[', ' ']) {
+        Some(index) => (&spec[..index], Some(spec[index..].trim())),
+        None => (spec, None),
+    };
+
+    let version = version
+        .map(|x| {
+            // bracketed constraints such as `numpy[version='>=1.26']`
+            x.strip_prefix("[version=")
+                .and_then(|x| x.strip_suffix(']'))
+                .map_or(x, |x| x.trim_matches(['\'', '"']))
+        })
+        .map(|x| {
+            // a single `=` is conda's fuzzy equality which is the default for bare versions
+            if x.starts_with("==") {
+                x
+            } else {
+                x.strip_prefix('=').unwrap_or(x)
+            }
+        })
+        .filter(|x| !x.is_empty())
+        .map(String::from);
+
+    (name.to_string(), CondaPackageOptions { channel, version })
+}
+
+/// Extracts the explicitly requested packages from `env export --from-history --json` output.
+fn extract_history_packages(stdout: &str) -> Result<BTreeMap<String, CondaPackageOptions>> {
+    let value: serde_json::Value = serde_json::from_str(stdout)?;
+
+    let Some(dependencies) = value.get("dependencies") else {
+        return Ok(BTreeMap::new());
+    };
+
+    Ok(dependencies
+        .as_array()
+        .ok_or(eyre!("the dependencies value should be an array"))?
+        .iter()
+        // pip dependencies are given as objects rather than match specs
+        .filter_map(|x| x.as_str())
+        .map(parse_package_spec)
+        .collect())
+}
+
+/// Parses the channels from `conda config --show channels` or `micromamba config list channels`
+/// output.
+fn parse_channels(stdout: &str) -> Vec<String> {
+    // example output:
+    // channels:
+    //   - conda-forge
+    //   - defaults
+    stdout
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("- "))
+        .map(|x| x.trim().to_string())
+        .collect()
+}
+
+fn pixi_home() -> Result<PathBuf> {
+    match std::env::var_os("PIXI_HOME") {
+        Some(pixi_home) => Ok(PathBuf::from(pixi_home)),
+        None => home::home_dir()
+            .map(|x| x.join(".pixi"))
+            .wrap_err("getting the home directory"),
+    }
+}
+
+/// Returns the global environments from the pixi global manifest along with the version and
+/// channel of the package each environment is named after.
+fn get_pixi_environments() -> Result<BTreeMap<String, CondaPackageOptions>> {
+    let path = pixi_home()?.join("manifests").join("pixi-global.toml");
+
+    if !path.is_file() {
+        return Ok(BTreeMap::new());
+    }
+
+    let manifest = std::fs::read_to_string(&path)
+        .wrap_err(eyre!("reading pixi global manifest: {}", path.display()))?
+        .parse::<toml::Table>()
+        .wrap_err(eyre!("parsing pixi global manifest: {}", path.display()))?;
+
+    Ok(extract_pixi_environments(&manifest))
+}
+
+fn extract_pixi_environments(manifest: &toml::Table) -> BTreeMap<String, CondaPackageOptions> {
+    // example manifest:
+    // [envs.python]
+    // channels = ["conda-forge"]
+    // dependencies = { python = "3.11.*" }
+    manifest
+        .get("envs")
+        .and_then(|x| x.as_table())
+        .into_iter()
+        .flatten()
+        .map(|(name, env)| {
+            let channel = env
+                .get("channels")
+                .and_then(|x| x.as_array())
+                .and_then(|x| x.first())
+                .and_then(|x| x.as_str())
+                .map(String::from);
+
+            let version = env
+                .get("dependencies")
+                .and_then(|x| x.get(name))
+                .and_then(|x| x.as_str().or_else(|| x.get("version")?.as_str()))
+                .filter(|x| *x != "*")
+                .map(String::from);
+
+            (name.clone(), CondaPackageOptions { channel, version })
+        })
+        .collect()
+}
+
+fn pixi_config_path() -> Result<PathBuf> {
+    Ok(pixi_home()?.join("config.toml"))
+}
+
+fn read_pixi_config() -> Result<DocumentMut> {
+    let path = pixi_config_path()?;
+
+    if !path.is_file() {
+        return Ok(DocumentMut::new());
+    }
+
+    std::fs::read_to_string(&path)
+        .wrap_err(eyre!("reading pixi config: {}", path.display()))?
+        .parse()
+        .wrap_err(eyre!("parsing pixi config: {}", path.display()))
+}
+
+fn write_pixi_config(document: &DocumentMut) -> Result<()> {
+    let path = pixi_config_path()?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(&path, document.to_string())
+        .wrap_err(eyre!("writing pixi config: {}", path.display()))
+}
+
+fn extract_pixi_channels(document: &DocumentMut) -> Vec<String> {
+    document
+        .get("default-channels")
+        .and_then(Item::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|x| x.as_str())
+        .map(String::from)
+        .collect()
+}
+
+fn set_pixi_channels(document: &mut DocumentMut, channels: &[String]) {
+    if channels.is_empty() {
+        document.remove("default-channels");
+    } else {
+        document["default-channels"] = value(channels.iter().collect::<Array>());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(channel: Option<&str>, version: Option<&str>) -> CondaPackageOptions {
+        CondaPackageOptions {
+            channel: channel.map(String::from),
+            version: version.map(String::from),
+        }
+    }
+
+    #[test]
+    fn package_specs() {
+        let spec = |x: &str| {
+            let (name, options) = parse_package_spec(x);
+            (name, options.channel, options.version)
+        };
+        let expected = |name: &str, channel: Option<&str>, version: Option<&str>| {
+            (
+                name.to_string(),
+                channel.map(String::from),
+                version.map(String::from),
+            )
+        };
+
+        assert_eq!(spec("numpy"), expected("numpy", None, None));
+        assert_eq!(
+            spec("conda-forge::numpy=1.26"),
+            expected("numpy", Some("conda-forge"), Some("1.26"))
+        );
+        assert_eq!(
+            spec("numpy==1.26.4"),
+            expected("numpy", None, Some("==1.26.4"))
+        );
+        assert_eq!(
+            spec("numpy>=1.26,<2"),
+            expected("numpy", None, Some(">=1.26,<2"))
+        );
+        assert_eq!(
+            spec("numpy[version='>=1.26']"),
+            expected("numpy", None, Some(">=1.26"))
+        );
+        assert_eq!(
+            spec("python 3.11.*"),
+            expected("python", None, Some("3.11.*"))
+        );
+    }
+
+    #[test]
+    fn pixi_environments() {
+        let manifest = indoc::indoc! {r#"
+            version = 1
+
+            [envs.python]
+            channels = ["conda-forge", "bioconda"]
+            dependencies = { python = "3.11.*" }
+
+            [envs.ripgrep]
+            channels = ["conda-forge"]
+            dependencies = { ripgrep = "*" }
+
+            [envs.samtools]
+            channels = ["bioconda"]
+            dependencies = { samtools = { version = ">=1.20", build = "*" } }
+
+            [envs.tools]
+            dependencies = { jq = "*" }
+        "#}
+        .parse::<toml::Table>()
+        .unwrap();
+
+        assert_eq!(
+            extract_pixi_environments(&manifest),
+            BTreeMap::from([
+                (
+                    "python".to_string(),
+                    options(Some("conda-forge"), Some("3.11.*"))
+                ),
+                ("ripgrep".to_string(), options(Some("conda-forge"), None)),
+                (
+                    "samtools".to_string(),
+                    options(Some("bioconda"), Some(">=1.20"))
+                ),
+                ("tools".to_string(), options(None, None)),
+            ])
+        );
+        assert!(extract_pixi_environments(&toml::Table::new()).is_empty());
+    }
+
+    #[test]
+    fn channels() {
+        // `conda config --show channels`
+        let conda = indoc::indoc! {"
+            channels:
+              - conda-forge
+              - defaults
+        "};
+        // `micromamba config list channels`
+        let micromamba = indoc::indoc! {"
+            channels:
+              - conda-forge
+              - bioconda
+        "};
+
+        assert_eq!(parse_channels(conda), ["conda-forge", "defaults"]);
+        assert_eq!(parse_channels(micromamba), ["conda-forge", "bioconda"]);
+        assert!(parse_channels("channels: []\n").is_empty());
+    }
+}
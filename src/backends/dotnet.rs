@@ -0,0 +1,313 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::cmd::{run_command, run_command_for_stdout};
+use crate::prelude::*;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
+pub struct Dotnet;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct DotnetConfig {}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DotnetPackageOptions {
+    /// The version of the tool to install such as `8.0.1`.
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DotnetRepoOptions {
+    /// The url of the package source such as `https://nuget.example.com/v3/index.json`.
+    pub url: Option<String>,
+}
+
+/// The default package source which is configured by the SDK rather than by the user.
+const DEFAULT_SOURCE: &str = "nuget.org";
+
+impl Backend for Dotnet {
+    type Config = DotnetConfig;
+    type PackageOptions = DotnetPackageOptions;
+    type RepoOptions = DotnetRepoOptions;
+
+    fn invalid_package_help_text() -> String {
+        indoc::formatdoc! {"
+            A dotnet tool may be invalid due to one of the following issues:
+                - the package name isn't lowercase, tool package ids are listed in lowercase by
+                  `dotnet tool list` so they must be lowercase in your group files too
+        "}
+    }
+
+    fn is_valid_package_name(package: &str) -> Option<bool> {
+        if package.chars().any(char::is_uppercase) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn get_all_packages(_: &Self::Config) -> Result<BTreeSet<String>> {
+        Err(eyre!("unimplemented"))
+    }
+
+    fn get_installed_packages(
+        config: &Self::Config,
+    ) -> Result<BTreeMap<String, Self::PackageOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let stdout = run_command_for_stdout(
+            ["dotnet", "tool", "list", "--global"],
+            Perms::Same,
+            StdErr::Show,
+        )?;
+
+        Ok(parse_tool_list(&stdout))
+    }
+
+    fn install_packages(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        for (package, options) in packages {
+            run_command(
+                ["dotnet", "tool", "install", "--global", package.as_str()]
+                    .into_iter()
+                    .chain(
+                        options
+                            .version
+                            .iter()
+                            .flat_map(|x| ["--version", x.as_str()]),
+                    ),
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn uninstall_packages(packages: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        for package in packages {
+            run_command(
+                ["dotnet", "tool", "uninstall", "--global", package.as_str()],
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_packages(packages: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        for package in packages {
+            run_command(
+                ["dotnet", "tool", "update", "--global", package.as_str()],
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_all_packages(no_confirm: bool, config: &Self::Config) -> Result<()> {
+        Self::update_packages(
+            &Self::get_installed_packages(config)?.into_keys().collect(),
+            no_confirm,
+            config,
+        )
+    }
+
+    fn clean_cache(config: &Self::Config) -> Result<()> {
+        Self::version(config).map_or(Ok(()), |_| {
+            run_command(["dotnet", "nuget", "locals", "all", "--clear"], Perms::Same)
+        })
+    }
+
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let stdout = run_command_for_stdout(
+            ["dotnet", "nuget", "list", "source", "--format", "detailed"],
+            Perms::Same,
+            StdErr::Show,
+        )?;
+
+        Ok(parse_sources(&stdout)
+            .into_iter()
+            .filter(|(name, _)| name != DEFAULT_SOURCE)
+            .collect())
+    }
+
+    fn add_repos(
+        repos: &BTreeMap<String, Self::RepoOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        for (repo, options) in repos {
+            let url = options
+                .url
+                .as_deref()
+                .ok_or(eyre!("dotnet repo {repo:?} is missing the url option"))?;
+
+            run_command(
+                [
+                    "dotnet",
+                    "nuget",
+                    "add",
+                    "source",
+                    url,
+                    "--name",
+                    repo.as_str(),
+                ],
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        for repo in repos {
+            run_command(
+                ["dotnet", "nuget", "remove", "source", repo.as_str()],
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn version(_: &Self::Config) -> Result<String> {
+        run_command_for_stdout(["dotnet", "--version"], Perms::Same, StdErr::Show)
+    }
+}
+
+/// Parses the tools and their versions from `dotnet tool list --global` output.
+fn parse_tool_list(stdout: &str) -> BTreeMap<String, DotnetPackageOptions> {
+    // example output:
+    // Package Id      Version      Commands
+    // -------------------------------------------
+    // dotnet-ef       8.0.1        dotnet-ef
+    stdout
+        .lines()
+        .skip(2)
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let package = parts.next()?;
+            let version = parts.next().map(String::from);
+
+            Some((package.to_string(), DotnetPackageOptions { version }))
+        })
+        .collect()
+}
+
+/// Parses the sources from `dotnet nuget list source --format detailed` output.
+fn parse_sources(stdout: &str) -> BTreeMap<String, DotnetRepoOptions> {
+    // example output:
+    // Registered Sources:
+    //   1.  nuget.org [Enabled]
+    //       https://api.nuget.org/v3/index.json
+    //   2.  corp [Disabled]
+    //       https://nuget.corp.example.com/v3/index.json
+    let mut sources = BTreeMap::new();
+    let mut lines = stdout.lines().map(str::trim);
+
+    while let Some(line) = lines.next() {
+        let Some((index, rest)) = line.split_once(". ") else {
+            continue;
+        };
+
+        if index.is_empty() || !index.chars().all(|x| x.is_ascii_digit()) {
+            continue;
+        }
+
+        let name = rest
+            .trim()
+            .rsplit_once(" [")
+            .map_or(rest.trim(), |(name, _)| name.trim());
+
+        sources.insert(
+            name.to_string(),
+            DotnetRepoOptions {
+                url: lines.next().map(String::from),
+            },
+        );
+    }
+
+    sources
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_global_tools() {
+        // `dotnet tool list --global`
+        let stdout = indoc::indoc! {"
+            Package Id                Version      Commands
+            -------------------------------------------------------
+            csharpier                 0.28.2       dotnet-csharpier
+            dotnet-ef                 8.0.1        dotnet-ef
+        "};
+
+        let version = |x: &str| DotnetPackageOptions {
+            version: Some(x.to_string()),
+        };
+
+        assert_eq!(
+            parse_tool_list(stdout),
+            BTreeMap::from([
+                ("csharpier".to_string(), version("0.28.2")),
+                ("dotnet-ef".to_string(), version("8.0.1")),
+            ])
+        );
+        assert!(
+            parse_tool_list(indoc::indoc! {"
+                Package Id      Version      Commands
+                -------------------------------------------
+            "})
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn parse_detailed_sources() {
+        // `dotnet nuget list source --format detailed`
+        let stdout = indoc::indoc! {"
+            Registered Sources:
+              1.  nuget.org [Enabled]
+                  https://api.nuget.org/v3/index.json
+              2.  corp feed [Disabled]
+                  https://nuget.corp.example.com/v3/index.json
+        "};
+
+        assert_eq!(
+            parse_sources(stdout),
+            BTreeMap::from([
+                (
+                    "corp feed".to_string(),
+                    DotnetRepoOptions {
+                        url: Some("https://nuget.corp.example.com/v3/index.json".to_string()),
+                    }
+                ),
+                (
+                    "nuget.org".to_string(),
+                    DotnetRepoOptions {
+                        url: Some("https://api.nuget.org/v3/index.json".to_string()),
+                    }
+                ),
+            ])
+        );
+        assert!(parse_sources("No sources found.").is_empty());
+    }
+}
@@ -0,0 +1,454 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::ErrorKind::NotFound;
+use std::io::Write;
+
+use color_eyre::Result;
+use color_eyre::eyre::{Context, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::cmd::{run_command, run_command_for_stdout};
+use crate::prelude::*;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
+pub struct Emerge;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct EmergeConfig {}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmergePackageOptions {
+    /// USE flags for the package such as `wayland` or `-X`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub use_flags: Vec<String>,
+    /// Keywords to accept for the package such as `~amd64`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmergeRepoOptions {
+    /// The sync url of an overlay which isn't in the official repository list.
+    pub url: Option<String>,
+    /// The sync type of an overlay with a `url`, defaults to `git`.
+    pub sync_type: Option<String>,
+}
+
+/// The file containing the explicitly installed packages.
+const PORTAGE_WORLD_FILE: &str = "/var/lib/portage/world";
+/// The file in the `package.use` directory owned by metapac.
+const METAPAC_PACKAGE_USE_FILE: &str = "/etc/portage/package.use/metapac";
+/// The file in the `package.accept_keywords` directory owned by metapac.
+const METAPAC_PACKAGE_KEYWORDS_FILE: &str = "/etc/portage/package.accept_keywords/metapac";
+/// The main gentoo repository which is configured by the system rather than as an overlay.
+const GENTOO_REPO: &str = "gentoo";
+
+impl Backend for Emerge {
+    type Config = EmergeConfig;
+    type PackageOptions = EmergePackageOptions;
+    type RepoOptions = EmergeRepoOptions;
+
+    fn invalid_package_help_text() -> String {
+        indoc::formatdoc! {"
+            An emerge package may be invalid due to one of the following issues:
+                - the package name does not use the explicit \"category/package\" format which is
+                  required by metapac in order to unambiguously match installed packages with those
+                  declared in your group files
+        "}
+    }
+
+    fn is_valid_package_name(package: &str) -> Option<bool> {
+        if package.contains('/') {
+            None
+        } else {
+            Some(false)
+        }
+    }
+
+    fn get_all_packages(_: &Self::Config) -> Result<BTreeSet<String>> {
+        Err(eyre!("unimplemented"))
+    }
+
+    fn get_installed_packages(
+        config: &Self::Config,
+    ) -> Result<BTreeMap<String, Self::PackageOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let world = read_optional(PORTAGE_WORLD_FILE)?;
+        let use_flags = read_atom_file(METAPAC_PACKAGE_USE_FILE)?;
+        let keywords = read_atom_file(METAPAC_PACKAGE_KEYWORDS_FILE)?;
+
+        Ok(world
+            .lines()
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+            .map(|package| {
+                (
+                    package.to_string(),
+                    Self::PackageOptions {
+                        use_flags: use_flags.get(package).cloned().unwrap_or_default(),
+                        keywords: keywords.get(package).cloned().unwrap_or_default(),
+                    },
+                )
+            })
+            .collect())
+    }
+
+    fn install_packages(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        no_confirm: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        // the USE flags and keywords must be in place before the packages are emerged
+        let mut use_flags = read_atom_file(METAPAC_PACKAGE_USE_FILE)?;
+        let mut keywords = read_atom_file(METAPAC_PACKAGE_KEYWORDS_FILE)?;
+        for (package, options) in packages {
+            set_or_remove(&mut use_flags, package, &options.use_flags);
+            set_or_remove(&mut keywords, package, &options.keywords);
+        }
+        write_atom_file(METAPAC_PACKAGE_USE_FILE, &use_flags)?;
+        write_atom_file(METAPAC_PACKAGE_KEYWORDS_FILE, &keywords)?;
+
+        run_command(
+            ["emerge", "--noreplace"]
+                .into_iter()
+                .chain((!no_confirm).then_some("--ask"))
+                .chain(packages.keys().map(String::as_str)),
+            Perms::Sudo,
+        )
+    }
+
+    fn package_option_changes(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: &Self::Config,
+    ) -> Result<Vec<String>> {
+        let (use_flags, keywords) = declared_atoms(packages);
+
+        Ok(atom_changes(
+            "USE flags",
+            &read_atom_file(METAPAC_PACKAGE_USE_FILE)?,
+            &use_flags,
+        )
+        .into_iter()
+        .chain(atom_changes(
+            "keywords",
+            &read_atom_file(METAPAC_PACKAGE_KEYWORDS_FILE)?,
+            &keywords,
+        ))
+        .collect())
+    }
+
+    fn sync_package_options(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        no_confirm: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        let old_use_flags = read_atom_file(METAPAC_PACKAGE_USE_FILE)?;
+        let old_keywords = read_atom_file(METAPAC_PACKAGE_KEYWORDS_FILE)?;
+
+        let (use_flags, keywords) = declared_atoms(packages);
+
+        if use_flags == old_use_flags && keywords == old_keywords {
+            return Ok(());
+        }
+
+        log::info!("updating the metapac portage package.use and package.accept_keywords files");
+
+        write_atom_file(METAPAC_PACKAGE_USE_FILE, &use_flags)?;
+        write_atom_file(METAPAC_PACKAGE_KEYWORDS_FILE, &keywords)?;
+
+        // rebuild any packages whose USE flags changed
+        run_command(
+            ["emerge", "--update", "--deep", "--newuse", "@world"]
+                .into_iter()
+                .chain((!no_confirm).then_some("--ask")),
+            Perms::Sudo,
+        )
+    }
+
+    fn uninstall_packages(
+        packages: &BTreeSet<String>,
+        no_confirm: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        // removing the packages from the world set lets depclean remove them along with any
+        // dependencies which are no longer needed
+        run_command(
+            ["emerge", "--deselect"]
+                .into_iter()
+                .chain(packages.iter().map(String::as_str)),
+            Perms::Sudo,
+        )?;
+
+        run_command(
+            ["emerge", "--depclean"]
+                .into_iter()
+                .chain((!no_confirm).then_some("--ask")),
+            Perms::Sudo,
+        )?;
+
+        let mut use_flags = read_atom_file(METAPAC_PACKAGE_USE_FILE)?;
+        let mut keywords = read_atom_file(METAPAC_PACKAGE_KEYWORDS_FILE)?;
+        for package in packages {
+            use_flags.remove(package);
+            keywords.remove(package);
+        }
+        write_atom_file(METAPAC_PACKAGE_USE_FILE, &use_flags)?;
+        write_atom_file(METAPAC_PACKAGE_KEYWORDS_FILE, &keywords)
+    }
+
+    fn update_packages(
+        packages: &BTreeSet<String>,
+        no_confirm: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        if !packages.is_empty() {
+            run_command(
+                ["emerge", "--update"]
+                    .into_iter()
+                    .chain((!no_confirm).then_some("--ask"))
+                    .chain(packages.iter().map(String::as_str)),
+                Perms::Sudo,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_all_packages(no_confirm: bool, _: &Self::Config) -> Result<()> {
+        run_command(
+            ["emerge", "--update", "--deep", "--newuse", "@world"]
+                .into_iter()
+                .chain((!no_confirm).then_some("--ask")),
+            Perms::Sudo,
+        )
+    }
+
+    fn clean_cache(config: &Self::Config) -> Result<()> {
+        // eclean is provided by gentoolkit which may not be installed
+        if Self::version(config).is_err()
+            || run_command_for_stdout(["eclean", "--version"], Perms::Same, StdErr::Hide).is_err()
+        {
+            return Ok(());
+        }
+
+        run_command(["eclean", "distfiles"], Perms::Sudo)
+    }
+
+    fn refresh(_: &Self::Config) -> Result<()> {
+        run_command(["emaint", "sync", "--auto"], Perms::Sudo)
+    }
+
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let stdout = run_command_for_stdout(
+            ["eselect", "repository", "list", "-i"],
+            Perms::Same,
+            StdErr::Show,
+        )?;
+
+        // example output:
+        // Available repositories:
+        //   [1]   gentoo # (https://gentoo.org/)
+        //   [2]   guru * (https://wiki.gentoo.org/wiki/Project:GURU)
+        Ok(stdout
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                parts.next().filter(|x| x.starts_with('['))?;
+                parts.next()
+            })
+            .filter(|x| *x != GENTOO_REPO)
+            .map(|x| (x.to_string(), Self::RepoOptions::default()))
+            .collect())
+    }
+
+    fn add_repos(
+        repos: &BTreeMap<String, Self::RepoOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        for (repo, options) in repos {
+            match &options.url {
+                Some(url) => run_command(
+                    [
+                        "eselect",
+                        "repository",
+                        "add",
+                        repo,
+                        options.sync_type.as_deref().unwrap_or("git"),
+                        url,
+                    ],
+                    Perms::Sudo,
+                )?,
+                None => run_command(["eselect", "repository", "enable", repo], Perms::Sudo)?,
+            }
+
+            run_command(["emaint", "sync", "--repo", repo], Perms::Sudo)?;
+        }
+
+        Ok(())
+    }
+
+    fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        if !repos.is_empty() {
+            run_command(
+                ["eselect", "repository", "remove"]
+                    .into_iter()
+                    .chain(repos.iter().map(String::as_str)),
+                Perms::Sudo,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn version(_: &Self::Config) -> Result<String> {
+        run_command_for_stdout(["emerge", "--version"], Perms::Same, StdErr::Show)
+    }
+}
+
+/// Returns the contents of the metapac `package.use` and `package.accept_keywords` files for the
+/// given packages. The files only contain the options from your group files so they are rewritten
+/// from scratch which also removes the options of packages no longer in your group files.
+fn declared_atoms(
+    packages: &BTreeMap<String, EmergePackageOptions>,
+) -> (BTreeMap<String, Vec<String>>, BTreeMap<String, Vec<String>>) {
+    let mut use_flags = BTreeMap::new();
+    let mut keywords = BTreeMap::new();
+    for (package, options) in packages {
+        set_or_remove(&mut use_flags, package, &options.use_flags);
+        set_or_remove(&mut keywords, package, &options.keywords);
+    }
+
+    (use_flags, keywords)
+}
+
+/// Describes the differences between the old and new contents of a metapac atom file.
+fn atom_changes(
+    kind: &str,
+    old: &BTreeMap<String, Vec<String>>,
+    new: &BTreeMap<String, Vec<String>>,
+) -> Vec<String> {
+    old.keys()
+        .chain(new.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter(|package| old.get(*package) != new.get(*package))
+        .map(|package| match new.get(package) {
+            Some(values) => format!("set the {kind} of {package} to {}", values.join(" ")),
+            None => format!("remove the {kind} of {package}"),
+        })
+        .collect()
+}
+
+fn set_or_remove(atoms: &mut BTreeMap<String, Vec<String>>, package: &str, values: &[String]) {
+    if values.is_empty() {
+        atoms.remove(package);
+    } else {
+        atoms.insert(package.to_string(), values.to_vec());
+    }
+}
+
+fn read_optional(path: &str) -> Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(err) if err.kind() == NotFound => Ok(String::new()),
+        Err(err) => Err(err).wrap_err(eyre!("reading {path:?}")),
+    }
+}
+
+/// Reads a portage file made up of lines in the form `category/package value1 value2`.
+fn read_atom_file(path: &str) -> Result<BTreeMap<String, Vec<String>>> {
+    Ok(read_optional(path)?
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let package = parts.next()?.to_string();
+
+            Some((package, parts.map(String::from).collect()))
+        })
+        .collect())
+}
+
+fn write_atom_file(path: &str, atoms: &BTreeMap<String, Vec<String>>) -> Result<()> {
+    if *atoms == read_atom_file(path)? {
+        return Ok(());
+    }
+
+    let mut file = tempfile::NamedTempFile::new()?;
+    writeln!(file, "# this file is managed by metapac")?;
+    for (package, values) in atoms {
+        writeln!(file, "{package} {}", values.join(" "))?;
+    }
+
+    run_command(
+        [
+            "install",
+            "-D",
+            "-m",
+            "644",
+            file.path()
+                .to_str()
+                .ok_or(eyre!("temporary file path should be valid utf-8"))?,
+            path,
+        ],
+        Perms::Sudo,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_atom_changes() {
+        let atoms = |x: &[(&str, &[&str])]| {
+            x.iter()
+                .map(|(package, values)| {
+                    (
+                        (*package).to_string(),
+                        values.iter().map(ToString::to_string).collect(),
+                    )
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let old = atoms(&[
+            ("app-editors/neovim", &["-lua"]),
+            ("media-video/mpv", &["wayland"]),
+            ("x11-wm/i3", &["doc"]),
+        ]);
+        let new = atoms(&[
+            ("app-editors/neovim", &["-lua"]),
+            ("media-video/mpv", &["wayland", "-X"]),
+            ("www-client/firefox", &["pgo"]),
+        ]);
+
+        assert_eq!(
+            atom_changes("USE flags", &old, &new),
+            [
+                "set the USE flags of media-video/mpv to wayland -X",
+                "set the USE flags of www-client/firefox to pgo",
+                "remove the USE flags of x11-wm/i3",
+            ]
+        );
+        assert!(atom_changes("keywords", &new, &new).is_empty());
+    }
+}
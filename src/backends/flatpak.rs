@@ -19,6 +19,115 @@ pub struct FlatpakConfig {}
 #[serde(deny_unknown_fields)]
 pub struct FlatpakPackageOptions {
     pub remote: Option<String>,
+    pub arch: Option<String>,
+    pub kind: Option<FlatpakKind>,
+    pub overrides: Option<FlatpakOverrides>,
+}
+
+/// The subset of `flatpak override` permissions that metapac manages. Permissions prefixed with
+/// `!` are revoked rather than granted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FlatpakOverrides {
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub filesystems: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub sockets: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub devices: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub talk_names: BTreeSet<String>,
+}
+impl FlatpakOverrides {
+    /// Parses the keyfile output of `flatpak override --show`.
+    fn parse(keyfile: &str) -> Self {
+        let mut overrides = Self::default();
+
+        let mut section = "";
+        for line in keyfile.lines().map(str::trim) {
+            if let Some(x) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+                section = x;
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            let list = || value.split(';').filter(|x| !x.is_empty()).map(String::from);
+
+            match (section, key) {
+                ("Context", "filesystems") => overrides.filesystems.extend(list()),
+                ("Context", "sockets") => overrides.sockets.extend(list()),
+                ("Context", "devices") => overrides.devices.extend(list()),
+                ("Environment", _) => {
+                    overrides.env.insert(key.to_string(), value.to_string());
+                }
+                ("Session Bus Policy", _) => match value {
+                    "talk" => {
+                        overrides.talk_names.insert(key.to_string());
+                    }
+                    "none" => {
+                        overrides.talk_names.insert(format!("!{key}"));
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+
+        overrides
+    }
+
+    fn to_cli_options(&self) -> Vec<String> {
+        let permission = |option: &str, negated_option: &str, x: &String| match x.strip_prefix('!')
+        {
+            Some(x) => format!("--{negated_option}={x}"),
+            None => format!("--{option}={x}"),
+        };
+
+        self.filesystems
+            .iter()
+            .map(|x| permission("filesystem", "nofilesystem", x))
+            .chain(
+                self.sockets
+                    .iter()
+                    .map(|x| permission("socket", "nosocket", x)),
+            )
+            .chain(
+                self.devices
+                    .iter()
+                    .map(|x| permission("device", "nodevice", x)),
+            )
+            .chain(
+                self.env
+                    .iter()
+                    .map(|(key, value)| format!("--env={key}={value}")),
+            )
+            .chain(
+                self.talk_names
+                    .iter()
+                    .map(|x| permission("talk-name", "no-talk-name", x)),
+            )
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlatpakKind {
+    App,
+    Runtime,
+}
+impl FlatpakKind {
+    fn to_cli_option(self) -> &'static str {
+        match self {
+            Self::App => "--app",
+            Self::Runtime => "--runtime",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -33,15 +142,80 @@ impl Backend for Flatpak {
     type RepoOptions = FlatpakRepoOptions;
 
     fn invalid_package_help_text() -> String {
-        String::new()
+        indoc::formatdoc! {"
+            A flatpak package may be invalid due to one of the following issues:
+                - the package name does not use the explicit \"installation:id\" format which is
+                  required by metapac in order to unambiguously match installed packages with those
+                  declared in your group files, where the installation is \"user\", \"system\" or
+                  the name of a custom installation, such as \"system:org.gimp.GIMP\", refs which
+                  aren't on the \"stable\" branch also need the branch, such as
+                  \"system:org.freedesktop.Platform//24.08\"
+                - the package is not available from any of the remotes configured for its
+                  installation
+                - the package is not available from the remote given in its \"remote\" option
+                - the local remote metadata is out of date and so doesn't yet contain the package,
+                  update it with `flatpak update --appstream`
+
+            You can check which remotes provide a package via `flatpak search <id>`.
+        "}
     }
 
-    fn is_valid_package_name(_: &str) -> Option<bool> {
-        None
+    fn is_valid_package_name(package: &str) -> Option<bool> {
+        match package.split_once(':') {
+            Some((installation, id)) if !installation.is_empty() && !id.is_empty() => None,
+            _ => Some(false),
+        }
+    }
+
+    fn get_all_packages(config: &Self::Config) -> Result<BTreeSet<String>> {
+        Ok(get_remote_packages(config)?
+            .into_iter()
+            .flat_map(|((installation, _), ids)| {
+                ids.into_iter()
+                    .map(move |id| format!("{installation}:{id}"))
+            })
+            .collect())
     }
 
-    fn get_all_packages(_: &Self::Config) -> Result<BTreeSet<String>> {
-        Err(eyre!("unimplemented"))
+    fn are_packages_valid(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        config: &Self::Config,
+    ) -> BTreeMap<String, Option<bool>> {
+        // listing the packages of every remote is slow so is avoided when there's nothing to
+        // validate
+        if packages.is_empty() {
+            return BTreeMap::new();
+        }
+
+        let remote_packages = get_remote_packages(config);
+
+        let mut output = BTreeMap::new();
+        for (package, options) in packages {
+            let valid = match (&remote_packages, package.split_once(':')) {
+                _ if Self::is_valid_package_name(package) == Some(false) => Some(false),
+                (Ok(remote_packages), Some((installation, id))) => {
+                    let mut remotes = remote_packages
+                        .iter()
+                        .filter(|((x, remote), _)| {
+                            x == installation && options.remote.as_ref().is_none_or(|x| x == remote)
+                        })
+                        .peekable();
+
+                    // the remote may not have been added yet if it is added by the same sync
+                    if remotes.peek().is_none() {
+                        None
+                    } else {
+                        let (id, _) = split_branch(id);
+                        Some(remotes.any(|(_, ids)| ids.contains(id)))
+                    }
+                }
+                _ => None,
+            };
+
+            output.insert(package.clone(), valid);
+        }
+
+        output
     }
 
     fn get_installed_packages(
@@ -51,29 +225,99 @@ impl Backend for Flatpak {
             return Ok(BTreeMap::new());
         }
 
+        let default_arch =
+            run_command_for_stdout(["flatpak", "--default-arch"], Perms::Same, StdErr::Show)?;
+        let default_arch = default_arch.trim();
+
+        let mut packages = BTreeMap::new();
+
         let apps = run_command_for_stdout(
             [
                 "flatpak",
                 "list",
-                "--app",
-                "--columns=installation,application,origin",
+                FlatpakKind::App.to_cli_option(),
+                "--columns=installation,application,origin,branch,arch",
             ],
             Perms::Same,
             StdErr::Show,
         )?;
 
-        let apps = apps.lines().map(|line| {
-            let parts = line.split_whitespace().collect::<Vec<_>>();
+        for line in apps.lines().filter(|x| !x.is_empty()) {
+            let [installation, application, origin, branch, arch] = parse_columns(line)?;
 
-            (
-                format!("{}:{}", parts[0], parts[1]),
+            packages.insert(
+                package_name(installation, application, branch),
                 Self::PackageOptions {
-                    remote: Some(parts[2].to_string()),
+                    remote: Some(origin.to_string()),
+                    arch: (arch != default_arch).then(|| arch.to_string()),
+                    kind: None,
+                    // overrides are only read during `metapac sync` as reading them needs a
+                    // command per app
+                    overrides: None,
                 },
-            )
-        });
+            );
+        }
 
-        Ok(apps.collect())
+        let runtimes = run_command_for_stdout(
+            [
+                "flatpak",
+                "list",
+                FlatpakKind::Runtime.to_cli_option(),
+                "--columns=installation,application,origin,branch,arch",
+            ],
+            Perms::Same,
+            StdErr::Show,
+        )?;
+        let runtimes = runtimes
+            .lines()
+            .filter(|x| !x.is_empty())
+            .map(parse_columns)
+            .collect::<Result<Vec<[&str; 5]>>>()?;
+
+        // runtimes that were explicitly installed are automatically pinned by flatpak whereas
+        // runtimes pulled in as dependencies of other refs are not, so we only return the pinned
+        // runtimes to avoid `metapac clean` trying to remove runtimes that are still required
+        let mut pinned = BTreeSet::new();
+        for installation in runtimes
+            .iter()
+            .map(|[installation, ..]| *installation)
+            .collect::<BTreeSet<_>>()
+        {
+            let pins = run_command_for_stdout(
+                [
+                    "flatpak".to_string(),
+                    "pin".to_string(),
+                    installation_option(installation),
+                ],
+                Perms::Same,
+                StdErr::Show,
+            )?;
+
+            for pin in pins.lines().map(str::trim) {
+                pinned.insert((installation, pin.to_string()));
+            }
+        }
+
+        for [installation, application, origin, branch, arch] in runtimes {
+            if !pinned.contains(&(
+                installation,
+                format!("runtime/{application}/{arch}/{branch}"),
+            )) {
+                continue;
+            }
+
+            packages.insert(
+                package_name(installation, application, branch),
+                Self::PackageOptions {
+                    remote: Some(origin.to_string()),
+                    arch: (arch != default_arch).then(|| arch.to_string()),
+                    kind: Some(FlatpakKind::Runtime),
+                    overrides: None,
+                },
+            );
+        }
+
+        Ok(packages)
     }
 
     fn install_packages(
@@ -82,35 +326,52 @@ impl Backend for Flatpak {
         _: &Self::Config,
     ) -> Result<()> {
         //group packages for faster installation and less y/n prompts
-        let mut groups: BTreeMap<(String, Option<String>), Vec<String>> = BTreeMap::new();
+        let mut groups: BTreeMap<(String, Option<String>, Option<FlatpakKind>), Vec<String>> =
+            BTreeMap::new();
         for (package, options) in packages {
             let (installation, name) = package.split_once(':').ok_or(eyre!(
                 "invalid flatpak package name: {package:?}, should be in form \"installation:package\", such as \"system:metapac\""
             ))?;
 
+            // a partial ref in the form `id/arch/branch` where an empty branch uses flatpak's
+            // default, otherwise flatpak understands the `id//branch` of the package name
+            let name = match (&options.arch, split_branch(name)) {
+                (Some(arch), (id, branch)) => {
+                    format!("{id}/{arch}/{}", branch.unwrap_or_default())
+                }
+                (None, _) => name.to_string(),
+            };
+
             groups
-                .entry((installation.to_string(), options.remote.clone()))
+                .entry((
+                    installation.to_string(),
+                    options.remote.clone(),
+                    options.kind,
+                ))
                 .or_default()
-                .push(name.to_string());
+                .push(name);
         }
 
-        for ((installation, remote), packages) in groups {
+        for ((installation, remote, kind), packages) in groups {
             run_command(
                 ["flatpak", "install"]
                     .into_iter()
                     .chain(no_confirm.then_some("--assumeyes"))
+                    .chain(kind.map(FlatpakKind::to_cli_option))
                     .map(ToString::to_string)
-                    .chain(match installation.as_str() {
-                        "user" => Some("--user".to_string()),
-                        "system" => Some("--system".to_string()),
-                        x => Some(format!("--installation={x}")),
-                    })
+                    .chain(Some(installation_option(&installation)))
                     .chain(remote)
                     .chain(packages),
                 Perms::Same,
             )?;
         }
 
+        for (package, options) in packages {
+            if let Some(overrides) = &options.overrides {
+                set_overrides(package, overrides)?;
+            }
+        }
+
         Ok(())
     }
 
@@ -138,11 +399,7 @@ impl Backend for Flatpak {
                     .into_iter()
                     .chain(no_confirm.then_some("--assumeyes"))
                     .map(ToString::to_string)
-                    .chain(match installation.as_str() {
-                        "user" => Some("--user".to_string()),
-                        "system" => Some("--system".to_string()),
-                        x => Some(format!("--installation={x}")),
-                    })
+                    .chain(Some(installation_option(&installation)))
                     .chain(packages),
                 Perms::Same,
             )?;
@@ -151,6 +408,42 @@ impl Backend for Flatpak {
         Ok(())
     }
 
+    fn package_option_changes(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: &Self::Config,
+    ) -> Result<Vec<String>> {
+        Ok(get_override_changes(packages)?
+            .into_iter()
+            .map(|(package, overrides)| {
+                let options = overrides.to_cli_options();
+                if options.is_empty() {
+                    format!("reset overrides for {package}")
+                } else {
+                    format!(
+                        "reset overrides for {package} and set: {}",
+                        options.join(" ")
+                    )
+                }
+            })
+            .collect())
+    }
+
+    fn sync_package_options(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        for (package, overrides) in get_override_changes(packages)? {
+            log::info!("updating flatpak overrides for {package:?}");
+
+            // we reset first so that overrides removed from the group files are also removed
+            reset_overrides(&package)?;
+            set_overrides(&package, &overrides)?;
+        }
+
+        Ok(())
+    }
+
     fn update_packages(
         packages: &BTreeSet<String>,
         no_confirm: bool,
@@ -175,11 +468,7 @@ impl Backend for Flatpak {
                     .into_iter()
                     .chain(no_confirm.then_some("--assumeyes"))
                     .map(ToString::to_string)
-                    .chain(match installation.as_str() {
-                        "user" => Some("--user".to_string()),
-                        "system" => Some("--system".to_string()),
-                        x => Some(format!("--installation={x}")),
-                    })
+                    .chain(Some(installation_option(&installation)))
                     .chain(packages),
                 Perms::Same,
             )?;
@@ -214,24 +503,13 @@ impl Backend for Flatpak {
             StdErr::Show,
         )?;
 
-        let repos = repos
+        repos
             .lines()
             // if there are no remotes an empty line is still returned
             // so we filter out empty lines
             .filter(|x| !x.is_empty())
-            .map(|line| {
-                let parts = line.split_whitespace().collect::<Vec<_>>();
-                let installation = parts[0].split(',').collect::<Vec<_>>()[0];
-                (
-                    format!("{}:{}", installation, parts[1]),
-                    FlatpakRepoOptions {
-                        url: Some(parts[2].to_string()),
-                    },
-                )
-            })
-            .collect();
-
-        Ok(repos)
+            .map(parse_remote)
+            .collect()
     }
 
     fn add_repos(
@@ -248,11 +526,7 @@ impl Backend for Flatpak {
                 ["flatpak", "remote-add"]
                     .into_iter()
                     .map(ToString::to_string)
-                    .chain(match installation {
-                        "user" => Some("--user".to_string()),
-                        "system" => Some("--system".to_string()),
-                        x => Some(format!("--installation={x}")),
-                    })
+                    .chain(Some(installation_option(installation)))
                     .chain([
                         name.to_string(),
                         options
@@ -278,11 +552,7 @@ impl Backend for Flatpak {
                 ["flatpak", "remote-delete"]
                     .into_iter()
                     .map(ToString::to_string)
-                    .chain(match installation {
-                        "user" => Some("--user".to_string()),
-                        "system" => Some("--system".to_string()),
-                        x => Some(format!("--installation={x}")),
-                    })
+                    .chain(Some(installation_option(installation)))
                     .chain([name.to_string()]),
                 Perms::Same,
             )?;
@@ -295,3 +565,350 @@ impl Backend for Flatpak {
         run_command_for_stdout(["flatpak", "--version"], Perms::Same, StdErr::Show)
     }
 }
+
+/// The branch used by flatpak refs which don't specify one.
+const DEFAULT_BRANCH: &str = "stable";
+
+/// Returns the package name of an installed ref in the form `installation:id`, or
+/// `installation:id//branch` for refs that aren't on the default branch so that multiple
+/// branches of the same ref can be installed side by side.
+fn package_name(installation: &str, application: &str, branch: &str) -> String {
+    if branch == DEFAULT_BRANCH {
+        format!("{installation}:{application}")
+    } else {
+        format!("{installation}:{application}//{branch}")
+    }
+}
+
+/// Splits the `id//branch` part of a package name into its id and optional branch.
+fn split_branch(name: &str) -> (&str, Option<&str>) {
+    match name.split_once("//") {
+        Some((id, branch)) => (id, Some(branch)),
+        None => (name, None),
+    }
+}
+
+fn installation_option(installation: &str) -> String {
+    match installation {
+        "user" => "--user".to_string(),
+        "system" => "--system".to_string(),
+        x => format!("--installation={x}"),
+    }
+}
+
+/// Returns the ids of the packages available from each configured remote, keyed by installation
+/// and remote.
+fn get_remote_packages(
+    config: &FlatpakConfig,
+) -> Result<BTreeMap<(String, String), BTreeSet<String>>> {
+    let mut remote_packages = BTreeMap::new();
+    for repo in Flatpak::get_installed_repos(config)?.keys() {
+        let (installation, remote) = repo
+            .split_once(':')
+            .ok_or(eyre!("unexpected flatpak remote: {repo:?}"))?;
+
+        let ids = run_command_for_stdout(
+            [
+                "flatpak".to_string(),
+                "remote-ls".to_string(),
+                installation_option(installation),
+                "--cached".to_string(),
+                // without `--all` extensions such as locales and debug info are hidden
+                "--all".to_string(),
+                "--columns=application".to_string(),
+                remote.to_string(),
+            ],
+            Perms::Same,
+            StdErr::Hide,
+        )?;
+
+        remote_packages.insert(
+            (installation.to_string(), remote.to_string()),
+            ids.lines()
+                .map(str::trim)
+                .filter(|x| !x.is_empty())
+                .map(String::from)
+                .collect(),
+        );
+    }
+
+    Ok(remote_packages)
+}
+
+/// Writing overrides for non-user installations requires root as unlike most other flatpak
+/// commands `flatpak override` does not do its own privilege escalation.
+fn installation_perms(installation: &str) -> Perms {
+    match installation {
+        "user" => Perms::Same,
+        _ => Perms::Sudo,
+    }
+}
+
+/// Returns the overrides to reset and re-apply for each of the given packages which is an installed
+/// app whose current overrides differ from its `overrides` option. Apps without the option are
+/// expected to have no overrides, so removing the option resets the app's overrides.
+fn get_override_changes(
+    packages: &BTreeMap<String, FlatpakPackageOptions>,
+) -> Result<BTreeMap<String, FlatpakOverrides>> {
+    // overrides only apply to apps
+    let packages = packages
+        .iter()
+        .filter(|(_, options)| options.kind != Some(FlatpakKind::Runtime))
+        .collect::<BTreeMap<_, _>>();
+
+    if packages.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    let apps = run_command_for_stdout(
+        [
+            "flatpak",
+            "list",
+            FlatpakKind::App.to_cli_option(),
+            "--columns=installation,application,branch",
+        ],
+        Perms::Same,
+        StdErr::Show,
+    )?;
+    let apps = apps
+        .lines()
+        .filter(|x| !x.is_empty())
+        .map(|line| {
+            parse_columns(line).map(|[installation, application, branch]| {
+                package_name(installation, application, branch)
+            })
+        })
+        .collect::<Result<BTreeSet<_>>>()?;
+
+    let mut changes = BTreeMap::new();
+    for (package, options) in packages {
+        // apps which aren't installed yet have their overrides set when they are installed
+        if !apps.contains(package) {
+            continue;
+        }
+
+        let (installation, name) = package.split_once(':').ok_or(eyre!(
+            "invalid flatpak package name: {package:?}, should be in form \"installation:package\", such as \"system:metapac\""
+        ))?;
+
+        let overrides = options.overrides.clone().unwrap_or_default();
+
+        if get_overrides(installation, split_branch(name).0)? != overrides {
+            changes.insert(package.clone(), overrides);
+        }
+    }
+
+    Ok(changes)
+}
+
+fn set_overrides(package: &str, overrides: &FlatpakOverrides) -> Result<()> {
+    let (installation, name) = package.split_once(':').ok_or(eyre!(
+        "invalid flatpak package name: {package:?}, should be in form \"installation:package\", such as \"system:metapac\""
+    ))?;
+
+    let options = overrides.to_cli_options();
+    if options.is_empty() {
+        return Ok(());
+    }
+
+    run_command(
+        ["flatpak".to_string(), "override".to_string()]
+            .into_iter()
+            .chain(Some(installation_option(installation)))
+            .chain(options)
+            .chain(Some(split_branch(name).0.to_string())),
+        installation_perms(installation),
+    )
+}
+
+fn reset_overrides(package: &str) -> Result<()> {
+    let (installation, name) = package.split_once(':').ok_or(eyre!(
+        "invalid flatpak package name: {package:?}, should be in form \"installation:package\", such as \"system:metapac\""
+    ))?;
+
+    run_command(
+        [
+            "flatpak".to_string(),
+            "override".to_string(),
+            installation_option(installation),
+            "--reset".to_string(),
+            split_branch(name).0.to_string(),
+        ],
+        installation_perms(installation),
+    )
+}
+
+fn get_overrides(installation: &str, application: &str) -> Result<FlatpakOverrides> {
+    let keyfile = run_command_for_stdout(
+        [
+            "flatpak".to_string(),
+            "override".to_string(),
+            installation_option(installation),
+            "--show".to_string(),
+            application.to_string(),
+        ],
+        Perms::Same,
+        StdErr::Show,
+    )?;
+
+    Ok(FlatpakOverrides::parse(&keyfile))
+}
+
+/// Parses a line of `flatpak list --columns=...` output which separates columns with tabs when
+/// not outputting to a terminal.
+fn parse_columns<const N: usize>(line: &str) -> Result<[&str; N]> {
+    line.split('\t')
+        .collect::<Vec<_>>()
+        .try_into()
+        .map_err(|_| {
+            eyre!("unexpected flatpak output: {line:?}, expected {N} tab separated columns")
+        })
+}
+
+/// Parses a line of `flatpak remotes --columns=options,name,url` output where the options column
+/// starts with the installation such as `system,gpg-verified`.
+fn parse_remote(line: &str) -> Result<(String, FlatpakRepoOptions)> {
+    let [options, name, url] = parse_columns(line)?;
+    let installation = options.split(',').next().unwrap_or(options);
+
+    Ok((
+        format!("{installation}:{name}"),
+        FlatpakRepoOptions {
+            url: Some(url.to_string()),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_overrides() {
+        // output of `flatpak override --user --show org.gimp.GIMP`
+        let keyfile = indoc::indoc! {"
+            [Context]
+            filesystems=xdg-download;!home;
+            sockets=wayland;!x11;
+
+            [Environment]
+            GTK_THEME=Adwaita:dark
+
+            [Session Bus Policy]
+            org.freedesktop.Flatpak=talk
+            org.kde.StatusNotifierWatcher=none
+        "};
+
+        assert_eq!(
+            FlatpakOverrides::parse(keyfile),
+            FlatpakOverrides {
+                filesystems: BTreeSet::from(["xdg-download".to_string(), "!home".to_string()]),
+                sockets: BTreeSet::from(["wayland".to_string(), "!x11".to_string()]),
+                devices: BTreeSet::new(),
+                env: BTreeMap::from([("GTK_THEME".to_string(), "Adwaita:dark".to_string())]),
+                talk_names: BTreeSet::from([
+                    "org.freedesktop.Flatpak".to_string(),
+                    "!org.kde.StatusNotifierWatcher".to_string(),
+                ]),
+            }
+        );
+        assert_eq!(FlatpakOverrides::parse(""), FlatpakOverrides::default());
+    }
+
+    #[test]
+    fn overrides_to_cli_options() {
+        let overrides = FlatpakOverrides {
+            filesystems: BTreeSet::from(["xdg-download".to_string(), "!home".to_string()]),
+            sockets: BTreeSet::from(["wayland".to_string()]),
+            devices: BTreeSet::from(["!dri".to_string()]),
+            env: BTreeMap::from([("GTK_THEME".to_string(), "Adwaita:dark".to_string())]),
+            talk_names: BTreeSet::from(["!org.kde.StatusNotifierWatcher".to_string()]),
+        };
+
+        assert_eq!(
+            overrides.to_cli_options(),
+            [
+                "--nofilesystem=home",
+                "--filesystem=xdg-download",
+                "--socket=wayland",
+                "--nodevice=dri",
+                "--env=GTK_THEME=Adwaita:dark",
+                "--no-talk-name=org.kde.StatusNotifierWatcher",
+            ]
+        );
+        assert!(FlatpakOverrides::default().to_cli_options().is_empty());
+    }
+
+    #[test]
+    fn parse_list_columns() {
+        // output of `flatpak list --runtime --columns=installation,application,origin,branch,arch`
+        let stdout = "system\torg.freedesktop.Platform\tflathub\t24.08\tx86_64\n\
+                      user\torg.gtk.Gtk3theme.adw-gtk3\tflathub\t3.22\tx86_64\n";
+
+        let columns = stdout
+            .lines()
+            .map(parse_columns)
+            .collect::<Result<Vec<[&str; 5]>>>()
+            .unwrap();
+
+        assert_eq!(
+            columns,
+            [
+                [
+                    "system",
+                    "org.freedesktop.Platform",
+                    "flathub",
+                    "24.08",
+                    "x86_64"
+                ],
+                [
+                    "user",
+                    "org.gtk.Gtk3theme.adw-gtk3",
+                    "flathub",
+                    "3.22",
+                    "x86_64"
+                ],
+            ]
+        );
+        assert!(parse_columns::<5>("system org.gimp.GIMP flathub stable x86_64").is_err());
+    }
+
+    #[test]
+    fn parse_remotes() {
+        // output of `flatpak remotes --columns=options,name,url`
+        assert_eq!(
+            parse_remote("system\tflathub\thttps://dl.flathub.org/repo/").unwrap(),
+            (
+                "system:flathub".to_string(),
+                FlatpakRepoOptions {
+                    url: Some("https://dl.flathub.org/repo/".to_string()),
+                }
+            )
+        );
+        assert_eq!(
+            parse_remote("user,disabled\tflathub-beta\thttps://dl.flathub.org/beta-repo/")
+                .unwrap()
+                .0,
+            "user:flathub-beta"
+        );
+        assert!(parse_remote("system flathub").is_err());
+    }
+
+    #[test]
+    fn branches_are_part_of_the_package_name() {
+        assert_eq!(
+            package_name("system", "org.gimp.GIMP", "stable"),
+            "system:org.gimp.GIMP"
+        );
+        assert_eq!(
+            package_name("system", "org.freedesktop.Platform", "24.08"),
+            "system:org.freedesktop.Platform//24.08"
+        );
+
+        assert_eq!(split_branch("org.gimp.GIMP"), ("org.gimp.GIMP", None));
+        assert_eq!(
+            split_branch("org.freedesktop.Platform//24.08"),
+            ("org.freedesktop.Platform", Some("24.08"))
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::cmd::{run_command, run_command_for_stdout};
+use crate::prelude::*;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
+pub struct Gem;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct GemConfig {
+    /// Whether to pass `--user-install` to gem commands so gems are installed into your home
+    /// directory rather than the system gem directory. This is off by default so gems installed
+    /// with a ruby from a version manager go into its own gem directory, but it must be turned on
+    /// to manage gems with your system's ruby without root.
+    #[serde(default)]
+    pub user_install: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GemPackageOptions {
+    /// The version requirement to install such as `1.60.0` or `~> 1.60`.
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GemRepoOptions {}
+
+/// The default gem source which is configured by rubygems rather than by the user.
+const DEFAULT_SOURCE: &str = "https://rubygems.org/";
+
+impl Backend for Gem {
+    type Config = GemConfig;
+    type PackageOptions = GemPackageOptions;
+    type RepoOptions = GemRepoOptions;
+
+    fn invalid_package_help_text() -> String {
+        String::new()
+    }
+
+    fn is_valid_package_name(_: &str) -> Option<bool> {
+        None
+    }
+
+    fn get_all_packages(_: &Self::Config) -> Result<BTreeSet<String>> {
+        Err(eyre!("unimplemented"))
+    }
+
+    fn get_installed_packages(
+        config: &Self::Config,
+    ) -> Result<BTreeMap<String, Self::PackageOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let stdout = run_command_for_stdout(["gem", "list", "--local"], Perms::Same, StdErr::Show)?;
+
+        Ok(parse_gem_list(&stdout))
+    }
+
+    fn install_packages(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        for (package, options) in packages {
+            run_command(
+                ["gem", "install", package.as_str()]
+                    .into_iter()
+                    .chain(config.user_install.then_some("--user-install"))
+                    .chain(
+                        options
+                            .version
+                            .iter()
+                            .flat_map(|x| ["--version", x.as_str()]),
+                    ),
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn uninstall_packages(
+        packages: &BTreeSet<String>,
+        _: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        if !packages.is_empty() {
+            run_command(
+                ["gem", "uninstall", "--all", "--executables"]
+                    .into_iter()
+                    .chain(config.user_install.then_some("--user-install"))
+                    .chain(packages.iter().map(String::as_str)),
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_packages(packages: &BTreeSet<String>, _: bool, config: &Self::Config) -> Result<()> {
+        if !packages.is_empty() {
+            run_command(
+                ["gem", "update"]
+                    .into_iter()
+                    .chain(config.user_install.then_some("--user-install"))
+                    .chain(packages.iter().map(String::as_str)),
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_all_packages(_: bool, config: &Self::Config) -> Result<()> {
+        run_command(
+            ["gem", "update"]
+                .into_iter()
+                .chain(config.user_install.then_some("--user-install")),
+            Perms::Same,
+        )
+    }
+
+    fn clean_cache(config: &Self::Config) -> Result<()> {
+        Self::version(config).map_or(Ok(()), |_| {
+            run_command(
+                ["gem", "cleanup"]
+                    .into_iter()
+                    .chain(config.user_install.then_some("--user-install")),
+                Perms::Same,
+            )
+        })
+    }
+
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let stdout =
+            run_command_for_stdout(["gem", "sources", "--list"], Perms::Same, StdErr::Show)?;
+
+        // example output:
+        // *** CURRENT SOURCES ***
+        //
+        // https://rubygems.org/
+        Ok(stdout
+            .lines()
+            .map(str::trim)
+            .filter(|x| !x.is_empty() && !x.starts_with("***") && *x != DEFAULT_SOURCE)
+            .map(|x| (x.to_string(), Self::RepoOptions {}))
+            .collect())
+    }
+
+    fn add_repos(
+        repos: &BTreeMap<String, Self::RepoOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        for repo in repos.keys() {
+            run_command(["gem", "sources", "--add", repo.as_str()], Perms::Same)?;
+        }
+
+        Ok(())
+    }
+
+    fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        for repo in repos {
+            run_command(["gem", "sources", "--remove", repo.as_str()], Perms::Same)?;
+        }
+
+        Ok(())
+    }
+
+    fn version(_: &Self::Config) -> Result<String> {
+        run_command_for_stdout(["gem", "--version"], Perms::Same, StdErr::Show)
+    }
+}
+
+/// Parses the output of `gem list --local` skipping default gems which are part of the ruby
+/// installation, the newest version which isn't a default version is used as the package's
+/// version.
+fn parse_gem_list(stdout: &str) -> BTreeMap<String, GemPackageOptions> {
+    // example output:
+    // bundler (default: 2.5.3)
+    // minitest (5.20.0, default: 5.16.0)
+    // rubocop (1.60.0, 1.59.0)
+    // nokogiri (1.16.0 x86_64-linux)
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (name, versions) = line.split_once(" (")?;
+            let versions = versions.strip_suffix(')')?;
+
+            // versions are listed newest first and may be followed by a platform, gems with only a
+            // default version are part of the ruby installation
+            let version = versions
+                .split(", ")
+                .find(|x| !x.starts_with("default:"))?
+                .split_whitespace()
+                .next()?;
+
+            Some((
+                name.to_string(),
+                GemPackageOptions {
+                    version: Some(version.to_string()),
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_local_gems() {
+        // `gem list --local`
+        let stdout = indoc::indoc! {"
+
+            *** LOCAL GEMS ***
+
+            bundler (default: 2.5.3)
+            minitest (5.20.0, default: 5.16.0)
+            nokogiri (1.16.0 x86_64-linux)
+            rubocop (1.60.0, 1.59.0)
+        "};
+
+        assert_eq!(
+            parse_gem_list(stdout)
+                .into_iter()
+                .map(|(name, options)| (name, options.version.unwrap()))
+                .collect::<Vec<_>>(),
+            [
+                ("minitest".to_string(), "5.20.0".to_string()),
+                ("nokogiri".to_string(), "1.16.0".to_string()),
+                ("rubocop".to_string(), "1.60.0".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::cmd::{run_command, run_command_for_stdout};
+use crate::prelude::*;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
+pub struct GhExtension;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct GhExtensionConfig {}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GhExtensionPackageOptions {
+    /// The release tag or commit to pin the extension to.
+    pub pin: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GhExtensionRepoOptions {}
+
+impl Backend for GhExtension {
+    type Config = GhExtensionConfig;
+    type PackageOptions = GhExtensionPackageOptions;
+    type RepoOptions = GhExtensionRepoOptions;
+
+    fn invalid_package_help_text() -> String {
+        indoc::formatdoc! {"
+            A gh extension may be invalid due to one of the following issues:
+                - the package name is not in the \"owner/gh-name\" format of the extension's
+                  repository such as \"dlvhdr/gh-dash\"
+        "}
+    }
+
+    fn is_valid_package_name(package: &str) -> Option<bool> {
+        let valid = package.split_once('/').is_some_and(|(owner, name)| {
+            !owner.is_empty()
+                && name.len() > "gh-".len()
+                && name.starts_with("gh-")
+                && !name.contains('/')
+                && !package.contains(char::is_whitespace)
+        });
+
+        if valid { None } else { Some(false) }
+    }
+
+    fn get_all_packages(_: &Self::Config) -> Result<BTreeSet<String>> {
+        Err(eyre!("unimplemented"))
+    }
+
+    fn get_installed_packages(
+        config: &Self::Config,
+    ) -> Result<BTreeMap<String, Self::PackageOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let stdout =
+            run_command_for_stdout(["gh", "extension", "list"], Perms::Same, StdErr::Show)?;
+
+        // example output:
+        // gh dash	dlvhdr/gh-dash	v4.7.1
+        // gh local	local-extension
+        Ok(stdout
+            .lines()
+            .filter_map(|line| line.split('\t').nth(1))
+            .map(str::trim)
+            // local extensions aren't installed from a repository
+            .filter(|x| x.contains('/'))
+            .map(|x| (x.to_string(), Self::PackageOptions { pin: None }))
+            .collect())
+    }
+
+    fn install_packages(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        for (package, options) in packages {
+            run_command(
+                ["gh", "extension", "install", package.as_str()]
+                    .into_iter()
+                    .chain(options.pin.iter().flat_map(|x| ["--pin", x.as_str()])),
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn uninstall_packages(packages: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        for package in packages {
+            run_command(
+                ["gh", "extension", "remove", extension_name(package)],
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_packages(packages: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        for package in packages {
+            run_command(
+                ["gh", "extension", "upgrade", extension_name(package)],
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_all_packages(_: bool, _: &Self::Config) -> Result<()> {
+        run_command(["gh", "extension", "upgrade", "--all"], Perms::Same)
+    }
+
+    fn clean_cache(_: &Self::Config) -> Result<()> {
+        Ok(())
+    }
+
+    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        Ok(BTreeMap::new())
+    }
+
+    fn add_repos(
+        repos: &BTreeMap<String, Self::RepoOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        if repos.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("unimplemented"))
+        }
+    }
+
+    fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        if repos.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("unimplemented"))
+        }
+    }
+
+    fn version(_: &Self::Config) -> Result<String> {
+        run_command_for_stdout(["gh", "--version"], Perms::Same, StdErr::Show)
+            .map(|x| x.lines().next().unwrap_or_default().to_string())
+    }
+}
+
+/// Returns the name gh knows an installed extension by, such as `gh-dash` for `dlvhdr/gh-dash`.
+fn extension_name(package: &str) -> &str {
+    package.rsplit_once('/').map_or(package, |(_, name)| name)
+}
@@ -0,0 +1,346 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use color_eyre::Result;
+use color_eyre::eyre::{Context, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::cmd::{run_command, run_command_for_stdout, run_command_with_env};
+use crate::prelude::*;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
+pub struct Go;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct GoConfig {}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GoPackageOptions {
+    /// The module version to install such as `v0.15.0`, defaults to `latest`.
+    pub version: Option<String>,
+    /// Environment variables to set when building the package such as `CGO_ENABLED = "0"`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GoRepoOptions {}
+
+/// An installed binary along with the package it was built from.
+struct Binary {
+    path: PathBuf,
+    version: Option<String>,
+    env: BTreeMap<String, String>,
+}
+
+/// The environment variables recorded in the build info which can be set with the `env` option.
+const BUILD_ENV: [&str; 2] = ["CGO_ENABLED", "GOEXPERIMENT"];
+
+impl Backend for Go {
+    type Config = GoConfig;
+    type PackageOptions = GoPackageOptions;
+    type RepoOptions = GoRepoOptions;
+
+    fn invalid_package_help_text() -> String {
+        indoc::formatdoc! {"
+            A go package may be invalid due to one of the following issues:
+                - the package name is not the full import path of a main package such as
+                  \"golang.org/x/tools/gopls\"
+                - the package name contains a version, use the `version` option instead
+        "}
+    }
+
+    fn is_valid_package_name(package: &str) -> Option<bool> {
+        if package.contains('@') || !package.contains('/') {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn get_all_packages(_: &Self::Config) -> Result<BTreeSet<String>> {
+        Err(eyre!("unimplemented"))
+    }
+
+    fn get_installed_packages(
+        config: &Self::Config,
+    ) -> Result<BTreeMap<String, Self::PackageOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        Ok(get_binaries()?
+            .into_iter()
+            .map(|(package, binary)| {
+                (
+                    package,
+                    Self::PackageOptions {
+                        version: binary.version,
+                        env: binary.env,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    fn install_packages(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        for (package, options) in packages {
+            run_command_with_env(
+                [
+                    "go".to_string(),
+                    "install".to_string(),
+                    format!(
+                        "{package}@{}",
+                        options.version.as_deref().unwrap_or("latest")
+                    ),
+                ],
+                &options.env,
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn uninstall_packages(packages: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        let binaries = get_binaries()?;
+
+        // go has no uninstall command so the installed binaries are removed directly
+        for package in packages {
+            let binary = binaries.get(package).ok_or(eyre!(
+                "no installed binary found for go package {package:?}"
+            ))?;
+
+            std::fs::remove_file(&binary.path)
+                .wrap_err(eyre!("removing go binary {:?}", binary.path))?;
+        }
+
+        Ok(())
+    }
+
+    fn update_packages(packages: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        let binaries = get_binaries()?;
+
+        // the packages are rebuilt with the same environment they were installed with
+        for package in packages {
+            let env = binaries
+                .get(package)
+                .map(|x| x.env.clone())
+                .unwrap_or_default();
+
+            run_command_with_env(
+                [
+                    "go".to_string(),
+                    "install".to_string(),
+                    format!("{package}@latest"),
+                ],
+                &env,
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_all_packages(no_confirm: bool, config: &Self::Config) -> Result<()> {
+        Self::update_packages(
+            &Self::get_installed_packages(config)?.into_keys().collect(),
+            no_confirm,
+            config,
+        )
+    }
+
+    fn clean_cache(config: &Self::Config) -> Result<()> {
+        Self::version(config).map_or(Ok(()), |_| {
+            run_command(["go", "clean", "-cache", "-modcache"], Perms::Same)
+        })
+    }
+
+    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        Ok(BTreeMap::new())
+    }
+
+    fn add_repos(
+        repos: &BTreeMap<String, Self::RepoOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        if repos.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("unimplemented"))
+        }
+    }
+
+    fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        if repos.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("unimplemented"))
+        }
+    }
+
+    fn version(_: &Self::Config) -> Result<String> {
+        run_command_for_stdout(["go", "version"], Perms::Same, StdErr::Show)
+    }
+}
+
+/// Returns the directory `go install` installs binaries into, which is `GOBIN` if set or else
+/// the `bin` directory of the first `GOPATH` entry.
+fn bin_dir() -> Result<PathBuf> {
+    let stdout =
+        run_command_for_stdout(["go", "env", "GOBIN", "GOPATH"], Perms::Same, StdErr::Show)?;
+
+    let mut lines = stdout.lines();
+    let gobin = lines.next().unwrap_or_default().trim();
+    let gopath = lines.next().unwrap_or_default().trim();
+
+    if !gobin.is_empty() {
+        return Ok(PathBuf::from(gobin));
+    }
+
+    std::env::split_paths(gopath)
+        .next()
+        .filter(|x| !x.as_os_str().is_empty())
+        .map(|x| x.join("bin"))
+        .ok_or(eyre!("neither GOBIN nor GOPATH are set"))
+}
+
+/// Returns the binaries in the go bin directory keyed by the package they were built from.
+fn get_binaries() -> Result<BTreeMap<String, Binary>> {
+    let bin_dir = bin_dir()?;
+
+    if !bin_dir.is_dir() {
+        return Ok(BTreeMap::new());
+    }
+
+    let stdout = run_command_for_stdout(
+        [
+            "go".to_string(),
+            "version".to_string(),
+            "-m".to_string(),
+            bin_dir.to_string_lossy().to_string(),
+        ],
+        Perms::Same,
+        StdErr::Hide,
+    )?;
+
+    Ok(parse_build_info(&stdout))
+}
+
+fn parse_build_info(stdout: &str) -> BTreeMap<String, Binary> {
+    // example output:
+    // /home/user/go/bin/gopls: go1.22.0
+    //         path    golang.org/x/tools/gopls
+    //         mod     golang.org/x/tools/gopls        v0.15.0 h1:...
+    //         dep     github.com/BurntSushi/toml      v1.2.1  h1:...
+    //         build   CGO_ENABLED=1
+    let mut blocks: Vec<(&str, Vec<&str>)> = Vec::new();
+    for line in stdout.lines() {
+        if line.starts_with(char::is_whitespace) {
+            if let Some((_, lines)) = blocks.last_mut() {
+                lines.push(line);
+            }
+        } else if let Some((path, _)) = line.rsplit_once(": ") {
+            blocks.push((path, Vec::new()));
+        }
+    }
+
+    blocks
+        .into_iter()
+        .filter_map(|(path, lines)| {
+            let field = |name: &str| {
+                lines.iter().find_map(|line| {
+                    let mut parts = line.split_whitespace();
+                    (parts.next()? == name).then_some(parts)
+                })
+            };
+
+            let package = field("path")?.next()?.to_string();
+            // binaries built from a local checkout have a `(devel)` version
+            let version = field("mod")
+                .and_then(|mut x| x.nth(1))
+                .filter(|x| *x != "(devel)")
+                .map(String::from);
+
+            let env = lines
+                .iter()
+                .filter_map(|line| line.trim().strip_prefix("build")?.trim().split_once('='))
+                .filter(|(key, value)| BUILD_ENV.contains(key) && !value.is_empty())
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+
+            Some((
+                package,
+                Binary {
+                    path: PathBuf::from(path),
+                    version,
+                    env,
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_go_version_output() {
+        // `go version -m ~/go/bin`
+        let stdout = indoc::indoc! {"
+            /home/user/go/bin/gopls: go1.22.0
+            \tpath\tgolang.org/x/tools/gopls
+            \tmod\tgolang.org/x/tools/gopls\tv0.15.0\th1:7tTmhVqJQmkc9Dt1o5z0rnnHDp3EFG/oQaJvvG3dOQE=
+            \tdep\tgithub.com/BurntSushi/toml\tv1.2.1\th1:9F2/+DoOYIOksmaJFPw1tGFy1eDnIJXg+UHjuD8lTak=
+            \tbuild\t-buildmode=exe
+            \tbuild\t-compiler=gc
+            \tbuild\tCGO_ENABLED=0
+            \tbuild\tGOARCH=amd64
+            \tbuild\tGOOS=linux
+            /home/user/go/bin/tool: go1.22.0
+            \tpath\texample.com/tool
+            \tmod\texample.com/tool\t(devel)\t
+            \tbuild\tCGO_ENABLED=1
+            \tbuild\tCGO_CFLAGS=
+            \tbuild\tGOEXPERIMENT=rangefunc
+        "};
+
+        let binaries = parse_build_info(stdout);
+
+        let gopls = &binaries["golang.org/x/tools/gopls"];
+        assert_eq!(gopls.path, PathBuf::from("/home/user/go/bin/gopls"));
+        assert_eq!(gopls.version.as_deref(), Some("v0.15.0"));
+        assert_eq!(
+            gopls.env,
+            BTreeMap::from([("CGO_ENABLED".to_string(), "0".to_string())])
+        );
+
+        let tool = &binaries["example.com/tool"];
+        assert_eq!(tool.version, None);
+        assert_eq!(
+            tool.env,
+            BTreeMap::from([
+                ("CGO_ENABLED".to_string(), "1".to_string()),
+                ("GOEXPERIMENT".to_string(), "rangefunc".to_string()),
+            ])
+        );
+        assert_eq!(binaries.len(), 2);
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use indoc::formatdoc;
+use serde::{Deserialize, Serialize};
+
+use crate::cmd::{run_command, run_command_for_stdout};
+use crate::prelude::*;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
+pub struct Guix;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct GuixConfig {
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GuixPackageOptions {
+    /// The version of the package to install, such as `2.78.0` or `2.78`.
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GuixRepoOptions {}
+
+/// The output guix installs when no output is given.
+const DEFAULT_OUTPUT: &str = "out";
+
+impl Backend for Guix {
+    type Config = GuixConfig;
+    type PackageOptions = GuixPackageOptions;
+    type RepoOptions = GuixRepoOptions;
+
+    fn invalid_package_help_text() -> String {
+        formatdoc! {"
+            A guix package may be invalid due to one of the following issues:
+                - package names must not be empty, contain whitespace, or contain '@', use the
+                  `version` option to install a specific version instead
+                - outputs other than the default \"out\" output are part of the package name such
+                  as \"glib:doc\"
+        "}
+    }
+
+    fn is_valid_package_name(package: &str) -> Option<bool> {
+        let has_whitespace = package.chars().any(char::is_whitespace);
+
+        if package.trim().is_empty() || package.contains('@') || has_whitespace {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn get_all_packages(_: &Self::Config) -> Result<BTreeSet<String>> {
+        let stdout = run_command_for_stdout(
+            ["guix", "package", "--list-available"],
+            Perms::Same,
+            StdErr::Hide,
+        )?;
+
+        // example output:
+        // glib	2.78.0	out,bin,doc,debug	gnu/packages/glib.scm:183:2
+        Ok(stdout
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split('\t');
+                let name = parts.next()?;
+                let outputs = parts.nth(1)?;
+
+                Some(
+                    outputs
+                        .split(',')
+                        .map(move |output| package_name(name, output)),
+                )
+            })
+            .flatten()
+            .collect())
+    }
+
+    fn get_installed_packages(
+        config: &Self::Config,
+    ) -> Result<BTreeMap<String, Self::PackageOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let stdout = run_command_for_stdout(
+            ["guix", "package", "--list-installed"]
+                .into_iter()
+                .map(String::from)
+                .chain(profile_args(config)),
+            Perms::Same,
+            StdErr::Show,
+        )?;
+
+        Ok(parse_installed_packages(&stdout))
+    }
+
+    fn install_packages(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        if !packages.is_empty() {
+            run_command(
+                ["guix", "install"]
+                    .into_iter()
+                    .map(String::from)
+                    .chain(profile_args(config))
+                    .chain(
+                        packages
+                            .iter()
+                            .map(|(package, options)| package_spec(package, options)),
+                    ),
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn uninstall_packages(
+        packages: &BTreeSet<String>,
+        _: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        if !packages.is_empty() {
+            run_command(
+                ["guix", "remove"]
+                    .into_iter()
+                    .map(String::from)
+                    .chain(profile_args(config))
+                    .chain(packages.iter().cloned()),
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_packages(packages: &BTreeSet<String>, _: bool, config: &Self::Config) -> Result<()> {
+        if !packages.is_empty() {
+            // `guix upgrade` takes regular expressions matching package names rather than outputs
+            let regexes = packages
+                .iter()
+                .map(|x| format!("^{}$", regex::escape(split_output(x).0)))
+                .collect::<BTreeSet<_>>();
+
+            run_command(
+                ["guix", "upgrade"]
+                    .into_iter()
+                    .map(String::from)
+                    .chain(profile_args(config))
+                    .chain(regexes),
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_all_packages(_: bool, config: &Self::Config) -> Result<()> {
+        run_command(
+            ["guix", "upgrade"]
+                .into_iter()
+                .map(String::from)
+                .chain(profile_args(config)),
+            Perms::Same,
+        )
+    }
+
+    fn clean_cache(config: &Self::Config) -> Result<()> {
+        Self::version(config).map_or(Ok(()), |_| run_command(["guix", "gc"], Perms::Same))
+    }
+
+    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        Ok(BTreeMap::new())
+    }
+
+    fn add_repos(
+        repos: &BTreeMap<String, Self::RepoOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        if repos.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("unimplemented"))
+        }
+    }
+
+    fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        if repos.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("unimplemented"))
+        }
+    }
+
+    fn version(_: &Self::Config) -> Result<String> {
+        run_command_for_stdout(["guix", "--version"], Perms::Same, StdErr::Show)
+            .map(|x| x.lines().next().unwrap_or_default().to_string())
+    }
+}
+
+fn profile_args(config: &GuixConfig) -> impl Iterator<Item = String> {
+    config
+        .profile
+        .iter()
+        .flat_map(|x| ["--profile".to_string(), x.clone()])
+}
+
+/// Returns the metapac package name of a package output, the default output is omitted.
+fn package_name(name: &str, output: &str) -> String {
+    if output == DEFAULT_OUTPUT {
+        name.to_string()
+    } else {
+        format!("{name}:{output}")
+    }
+}
+
+/// Splits a package name such as `glib:doc` into its name and optional output.
+fn split_output(package: &str) -> (&str, Option<&str>) {
+    match package.split_once(':') {
+        Some((name, output)) => (name, Some(output)),
+        None => (package, None),
+    }
+}
+
+/// Returns the guix package spec such as `glib@2.78.0:doc` for a package.
+fn package_spec(package: &str, options: &GuixPackageOptions) -> String {
+    let (name, output) = split_output(package);
+
+    format!(
+        "{name}{}{}",
+        options
+            .version
+            .as_ref()
+            .map(|x| format!("@{x}"))
+            .unwrap_or_default(),
+        output.map(|x| format!(":{x}")).unwrap_or_default()
+    )
+}
+
+fn parse_installed_packages(stdout: &str) -> BTreeMap<String, GuixPackageOptions> {
+    // example output:
+    // glib	2.78.0	doc	/gnu/store/...-glib-2.78.0-doc
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('\t');
+            let name = parts.next()?;
+            let version = parts.next()?.to_string();
+            let output = parts.next()?;
+
+            Some((
+                package_name(name, output),
+                GuixPackageOptions {
+                    version: Some(version),
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_list_installed() {
+        // `guix package --list-installed`
+        let stdout = indoc::indoc! {"
+            glib\t2.78.0\tout\t/gnu/store/4x4bd2wn3qvz8kvbd4f1ar7zygcyl7zf-glib-2.78.0
+            glib\t2.78.0\tdoc\t/gnu/store/lx1xr7cc4v2n1zq3m0y8hhw1ldq7d7r6-glib-2.78.0-doc
+            hello\t2.12.1\tout\t/gnu/store/6fbh8phmp3izay6c0dpggpxhcjn4xlm5-hello-2.12.1
+        "};
+
+        let version = |x: &str| GuixPackageOptions {
+            version: Some(x.to_string()),
+        };
+
+        assert_eq!(
+            parse_installed_packages(stdout)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            [
+                ("glib".to_string(), version("2.78.0")),
+                ("glib:doc".to_string(), version("2.78.0")),
+                ("hello".to_string(), version("2.12.1")),
+            ]
+        );
+        assert!(parse_installed_packages("").is_empty());
+    }
+}
@@ -24,7 +24,9 @@ pub struct MisePackageOptions {
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct MiseRepoOptions {}
+pub struct MiseRepoOptions {
+    pub url: Option<String>,
+}
 
 impl Backend for Mise {
     type Config = MiseConfig;
@@ -57,6 +59,36 @@ impl Backend for Mise {
             .collect())
     }
 
+    fn are_packages_valid(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        config: &Self::Config,
+    ) -> BTreeMap<String, Option<bool>> {
+        let registry = Self::get_all_packages(config).ok();
+        let plugins = get_plugins().ok();
+
+        packages
+            .keys()
+            .map(|package| {
+                let valid = match required_plugin(package) {
+                    Some(plugin) => {
+                        if plugins.as_ref().is_some_and(|x| x.contains_key(plugin))
+                            || registry.as_ref().is_some_and(|x| x.contains(package))
+                        {
+                            Some(true)
+                        } else {
+                            // the plugin may be added as a repo before the package is installed,
+                            // which is checked by `install_packages`
+                            None
+                        }
+                    }
+                    None => None,
+                };
+
+                (package.clone(), valid)
+            })
+            .collect()
+    }
+
     fn get_installed_packages(
         config: &Self::Config,
     ) -> Result<BTreeMap<String, Self::PackageOptions>> {
@@ -109,8 +141,28 @@ impl Backend for Mise {
     fn install_packages(
         packages: &BTreeMap<String, Self::PackageOptions>,
         no_confirm: bool,
-        _: &Self::Config,
+        config: &Self::Config,
     ) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        // the registry may be unavailable when offline, in which case we leave it to mise to
+        // report missing plugins
+        let registry = Self::get_all_packages(config).ok();
+        let plugins = get_plugins()?;
+
+        for package in packages.keys() {
+            if let Some(plugin) = required_plugin(package)
+                && !plugins.contains_key(plugin)
+                && registry.as_ref().is_some_and(|x| !x.contains(package))
+            {
+                return Err(eyre!(
+                    "the mise plugin {plugin:?} for package {package:?} is not installed and is not in the mise registry, add it as a mise repo with its url"
+                ));
+            }
+        }
+
         for (package, options) in packages {
             let package = format!("{package}@{}", options.version.as_deref().unwrap_or(""));
             run_command(
@@ -153,8 +205,19 @@ impl Backend for Mise {
         run_command(["mise", "plugins", "update"], Perms::Same)
     }
 
-    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
-        Ok(BTreeMap::new())
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let registry = Self::get_all_packages(config).unwrap_or_default();
+        let installed = Self::get_installed_packages(config)?;
+
+        Ok(filter_registry_plugins(
+            get_plugins()?,
+            &registry,
+            installed.keys(),
+        ))
     }
 
     fn add_repos(
@@ -162,22 +225,124 @@ impl Backend for Mise {
         _: bool,
         _: &Self::Config,
     ) -> Result<()> {
-        if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+        for (repo, options) in repos {
+            run_command(
+                ["mise", "plugins", "install", repo.as_str()]
+                    .into_iter()
+                    .chain(options.url.as_deref()),
+                Perms::Same,
+            )?;
         }
+
+        Ok(())
     }
 
     fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
-        if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+        for repo in repos {
+            run_command(["mise", "plugins", "uninstall", repo.as_str()], Perms::Same)?;
         }
+
+        Ok(())
     }
 
     fn version(_: &Self::Config) -> Result<String> {
         run_command_for_stdout(["mise", "--version"], Perms::Same, StdErr::Show)
     }
 }
+
+/// The backends built into mise which don't need a plugin to be installed.
+///
+/// This needs to be kept in sync with the backends listed in the mise docs
+/// (<https://mise.jdx.dev/dev-tools/backends/>), otherwise packages from a newly added backend
+/// are mistaken for packages that need a plugin of the same name.
+const BUILTIN_BACKENDS: [&str; 16] = [
+    "aqua", "asdf", "cargo", "conda", "core", "dotnet", "gem", "github", "gitlab", "go", "http",
+    "npm", "pipx", "spm", "ubi", "vfox",
+];
+
+/// Returns the name of the plugin a package needs, which is either the package itself for
+/// plugins such as `asdf` plugins, or the prefix of packages from plugin backends such as
+/// `my-plugin:tool`.
+fn required_plugin(package: &str) -> Option<&str> {
+    match package.split_once(':') {
+        Some((backend, _)) if BUILTIN_BACKENDS.contains(&backend) => None,
+        Some((plugin, _)) => Some(plugin),
+        None => Some(package),
+    }
+}
+
+/// Removes the plugins which mise installed by itself for installed registry tools, since reporting
+/// those as repos would make every tool using one show up as an unmanaged repo too.
+fn filter_registry_plugins<'a>(
+    plugins: BTreeMap<String, MiseRepoOptions>,
+    registry: &BTreeSet<String>,
+    installed: impl IntoIterator<Item = &'a String>,
+) -> BTreeMap<String, MiseRepoOptions> {
+    let required = installed
+        .into_iter()
+        .filter_map(|package| required_plugin(package))
+        .collect::<BTreeSet<_>>();
+
+    plugins
+        .into_iter()
+        .filter(|(plugin, _)| !(registry.contains(plugin) && required.contains(plugin.as_str())))
+        .collect()
+}
+
+fn get_plugins() -> Result<BTreeMap<String, MiseRepoOptions>> {
+    let stdout = run_command_for_stdout(
+        ["mise", "plugins", "ls", "--urls"],
+        Perms::Same,
+        StdErr::Hide,
+    )?;
+
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?.to_string();
+            let url = parts.next().map(String::from);
+
+            Some((name, MiseRepoOptions { url }))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn required_plugins() {
+        // builtin backends don't need a plugin
+        assert_eq!(required_plugin("aqua:cli/cli"), None);
+        assert_eq!(required_plugin("cargo:ripgrep"), None);
+        assert_eq!(required_plugin("npm:prettier"), None);
+
+        // plugin backends need the plugin in their prefix
+        assert_eq!(required_plugin("vfox-pnpm:pnpm"), Some("vfox-pnpm"));
+        assert_eq!(required_plugin("my-plugin:tool"), Some("my-plugin"));
+
+        // bare asdf tools need a plugin named after the tool
+        assert_eq!(required_plugin("terraform"), Some("terraform"));
+    }
+
+    #[test]
+    fn registry_plugins_are_filtered() {
+        let plugins = ["terraform", "my-plugin", "unused"]
+            .into_iter()
+            .map(|x| (x.to_string(), MiseRepoOptions::default()))
+            .collect::<BTreeMap<_, _>>();
+        let registry = BTreeSet::from(["terraform".to_string(), "unused".to_string()]);
+        let installed = ["terraform".to_string(), "my-plugin:tool".to_string()];
+
+        // `terraform` was installed by mise for a registry tool, `my-plugin` isn't in the registry
+        // so was added by the user and `unused` isn't needed by any installed tool
+        assert_eq!(
+            filter_registry_plugins(plugins, &registry, installed.iter())
+                .into_keys()
+                .collect::<Vec<_>>(),
+            ["my-plugin", "unused"]
+        );
+    }
+}
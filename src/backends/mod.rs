@@ -1,17 +1,28 @@
 pub mod all;
+pub mod apk;
 pub mod apt;
 pub mod arch;
 pub mod brew;
 pub mod bun;
 pub mod cargo;
+pub mod conda;
 pub mod dnf;
+pub mod dotnet;
+pub mod emerge;
 pub mod flatpak;
+pub mod gem;
+pub mod ghextension;
+pub mod go;
+pub mod guix;
 pub mod mas;
 pub mod mise;
 pub mod nix;
+pub mod node;
 pub mod npm;
 pub mod pipx;
 pub mod pnpm;
+pub mod rpmostree;
+pub mod rustup;
 pub mod scoop;
 pub mod snap;
 pub mod uv;
@@ -28,19 +39,29 @@ use color_eyre::Result;
 macro_rules! apply_backends {
     ($macro:ident) => {
         $macro! {
+        (Apk, apk),
         (Apt, apt),
         (Arch, arch),
         (Brew, brew),
         (Bun, bun),
         (Cargo, cargo),
+        (Conda, conda),
         (Dnf, dnf),
+        (Dotnet, dotnet),
+        (Emerge, emerge),
         (Flatpak, flatpak),
+        (Gem, gem),
+        (GhExtension, ghextension),
+        (Go, go),
+        (Guix, guix),
         (Mas, mas),
         (Mise, mise),
         (Nix, nix),
         (Npm, npm),
         (Pipx, pipx),
         (Pnpm, pnpm),
+        (RpmOstree, rpmostree),
+        (Rustup, rustup),
         (Scoop, scoop),
         (Snap, snap),
         (Uv, uv),
@@ -78,6 +99,43 @@ pub trait Backend {
     /// configured.
     fn get_all_packages(config: &Self::Config) -> Result<BTreeSet<String>>;
 
+    /// If possible the backend will attempt to decide whether the given packages are valid
+    /// packages or not when installed with their given options.
+    ///
+    /// Validity is defined as the package being able to be installed on the current system as the
+    /// package manager is currently configured.
+    ///
+    /// - `Some(true)` means the package is valid
+    /// - `Some(false)` means the package is invalid
+    /// - `None` means the package could be valid or invalid.
+    ///
+    /// Backends with package options that limit where a package can be installed from should
+    /// override this method to take those options into account.
+    fn are_packages_valid(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        config: &Self::Config,
+    ) -> BTreeMap<String, Option<bool>> {
+        let existing_packages = Self::get_all_packages(config);
+
+        let mut output = BTreeMap::new();
+        for package in packages.keys() {
+            let valid = match &existing_packages {
+                Ok(existing_packages) => Some(existing_packages.contains(package)),
+                Err(_) => {
+                    if Self::is_valid_package_name(package) == Some(false) {
+                        Some(false)
+                    } else {
+                        None
+                    }
+                }
+            };
+
+            output.insert(package.clone(), valid);
+        }
+
+        output
+    }
+
     /// Attempts to return packages which are explicitly installed along with their options.
     ///
     /// If a backend cannot distinguish between explicit and implicit packages then it should
@@ -111,6 +169,32 @@ pub trait Backend {
         config: &Self::Config,
     ) -> Result<()>;
 
+    /// Attempts to return a human readable description of each change that
+    /// [`Backend::sync_package_options`] would make for the given `packages`, so that they can be
+    /// shown to the user for confirmation before they are made.
+    ///
+    /// Backends which override [`Backend::sync_package_options`] should also override this
+    /// method.
+    fn package_option_changes(
+        _packages: &BTreeMap<String, Self::PackageOptions>,
+        _config: &Self::Config,
+    ) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Attempts to bring the options that the given installed `packages` are currently installed
+    /// with in line with the given options, optionally without confirmation using `no_confirm`.
+    ///
+    /// This is only for options which can be changed without reinstalling a package. Backends
+    /// without any such options should have a no-op implementation.
+    fn sync_package_options(
+        _packages: &BTreeMap<String, Self::PackageOptions>,
+        _no_confirm: bool,
+        _config: &Self::Config,
+    ) -> Result<()> {
+        Ok(())
+    }
+
     /// Attempts to update the given `packages`, optionally without confirmation using
     /// `no_confirm`.
     ///
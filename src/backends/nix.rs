@@ -20,6 +20,8 @@ pub struct NixConfig {
     pub impure: bool,
     #[serde(default)]
     pub accept_flake_config: bool,
+    #[serde(default)]
+    pub validate_packages: bool,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -31,7 +33,11 @@ pub struct NixPackageOptions {
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct NixRepoOptions {}
+pub struct NixRepoOptions {
+    /// The flake reference the registry entry points to, such as
+    /// `github:NixOS/nixpkgs/nixos-24.05`.
+    pub url: Option<String>,
+}
 
 impl Backend for Nix {
     type Config = NixConfig;
@@ -59,8 +65,56 @@ impl Backend for Nix {
         }
     }
 
-    fn get_all_packages(_: &Self::Config) -> Result<BTreeSet<String>> {
-        Err(eyre!("unimplemented"))
+    /// Only nixpkgs is searched since that is where packages without an installable are
+    /// installed from, the flakes of repos aren't validated.
+    fn get_all_packages(config: &Self::Config) -> Result<BTreeSet<String>> {
+        let args = ["nix", "search", "nixpkgs", "^", "--json", "--quiet"]
+            .into_iter()
+            .map(String::from)
+            .chain(config.impure.then_some("--impure".to_string()))
+            .chain(
+                config
+                    .accept_flake_config
+                    .then_some("--accept-flake-config".to_string()),
+            )
+            .collect::<Vec<_>>();
+
+        let output = run_command_for_stdout(args, Perms::Same, StdErr::Hide)?;
+
+        parse_search_packages(&output)
+    }
+
+    fn are_packages_valid(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        config: &Self::Config,
+    ) -> BTreeMap<String, Option<bool>> {
+        // only packages installed from nixpkgs by name can be validated, explicit installables
+        // may point at any flake including ones added as repos during the same sync. Searching
+        // nixpkgs is slow so it is opt-in
+        let existing_packages = (config.validate_packages
+            && packages.values().any(|x| x.installable.is_none()))
+        .then(|| Self::get_all_packages(config).ok())
+        .flatten();
+
+        packages
+            .iter()
+            .map(|(package, options)| {
+                let valid = if Self::is_valid_package_name(package) == Some(false) {
+                    Some(false)
+                } else if options.installable.is_some() {
+                    None
+                } else {
+                    // profile element names don't always match attribute names, so a package
+                    // that can't be found isn't necessarily invalid
+                    existing_packages
+                        .as_ref()
+                        .is_some_and(|x| x.contains(package))
+                        .then_some(true)
+                };
+
+                (package.clone(), valid)
+            })
+            .collect()
     }
 
     fn get_installed_packages(
@@ -199,8 +253,15 @@ impl Backend for Nix {
         Self::version(config).map_or(Ok(()), |_| run_command(["nix", "store", "gc"], Perms::Same))
     }
 
-    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
-        Ok(BTreeMap::new())
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let output =
+            run_command_for_stdout(["nix", "registry", "list"], Perms::Same, StdErr::Show)?;
+
+        Ok(parse_user_registry(&output))
     }
 
     fn add_repos(
@@ -208,19 +269,24 @@ impl Backend for Nix {
         _: bool,
         _: &Self::Config,
     ) -> Result<()> {
-        if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+        for (repo, options) in repos {
+            let url = options
+                .url
+                .as_deref()
+                .ok_or(eyre!("nix repo {repo:?} is missing the url option"))?;
+
+            run_command(["nix", "registry", "add", repo.as_str(), url], Perms::Same)?;
         }
+
+        Ok(())
     }
 
     fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
-        if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+        for repo in repos {
+            run_command(["nix", "registry", "remove", repo.as_str()], Perms::Same)?;
         }
+
+        Ok(())
     }
 
     fn version(_: &Self::Config) -> Result<String> {
@@ -278,3 +344,103 @@ fn parse_installed_packages(stdout: &str) -> Result<BTreeMap<String, NixPackageO
         })
         .collect()
 }
+
+/// Parses the entries of the user registry from `nix registry list` output, the system and
+/// global registries aren't managed by metapac.
+fn parse_user_registry(stdout: &str) -> BTreeMap<String, NixRepoOptions> {
+    // example output:
+    // user   flake:corp git+https://git.example.com/corp/flake
+    // global flake:nixpkgs github:NixOS/nixpkgs/nixpkgs-unstable
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+
+            if parts.next()? != "user" {
+                return None;
+            }
+
+            let name = parts.next()?;
+            let url = parts.next().map(String::from);
+
+            Some((
+                name.strip_prefix("flake:").unwrap_or(name).to_string(),
+                NixRepoOptions { url },
+            ))
+        })
+        .collect()
+}
+
+fn parse_search_packages(stdout: &str) -> Result<BTreeSet<String>> {
+    let search: serde_json::Value = serde_json::from_str(stdout)?;
+
+    // keys are in the form `legacyPackages.<system>.<attribute path>`
+    Ok(search
+        .as_object()
+        .ok_or(eyre!("expected nix search output to be an object"))?
+        .keys()
+        .filter_map(|x| x.splitn(3, '.').nth(2))
+        .map(String::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_registry() {
+        let stdout = indoc::indoc! {"
+            user   flake:corp git+https://git.example.com/corp/flake
+            user   flake:local path:/home/user/flake
+            system flake:nixpkgs path:/nix/store/abc-source
+            global flake:nixpkgs github:NixOS/nixpkgs/nixpkgs-unstable
+            global flake:home-manager github:nix-community/home-manager
+        "};
+
+        assert_eq!(
+            parse_user_registry(stdout),
+            BTreeMap::from([
+                (
+                    "corp".to_string(),
+                    NixRepoOptions {
+                        url: Some("git+https://git.example.com/corp/flake".to_string())
+                    }
+                ),
+                (
+                    "local".to_string(),
+                    NixRepoOptions {
+                        url: Some("path:/home/user/flake".to_string())
+                    }
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn search_packages() {
+        let stdout = indoc::indoc! {r#"
+            {
+              "legacyPackages.x86_64-linux.hello": {
+                "description": "Program that produces a familiar, friendly greeting",
+                "pname": "hello",
+                "version": "2.12.1"
+              },
+              "legacyPackages.x86_64-linux.python312Packages.requests": {
+                "description": "HTTP library for Python",
+                "pname": "python3.12-requests",
+                "version": "2.32.3"
+              }
+            }
+        "#};
+
+        assert_eq!(
+            parse_search_packages(stdout).unwrap(),
+            BTreeSet::from([
+                "hello".to_string(),
+                "python312Packages.requests".to_string()
+            ])
+        );
+        assert!(parse_search_packages("[]").is_err());
+    }
+}
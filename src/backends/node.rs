@@ -0,0 +1,326 @@
+//! Functionality shared between the node package manager backends: `npm`, `pnpm`, `yarn` and
+//! `bun`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use color_eyre::Result;
+use color_eyre::eyre::{Context, eyre};
+use serde_json::Value;
+use toml_edit::{DocumentMut, Item, Table, value};
+
+/// Extracts the package names and versions from the `dependencies` object of either a `list
+/// --global --json` output or a global `package.json` file.
+pub fn extract_dependencies(value: &Value) -> Result<BTreeMap<String, Option<String>>> {
+    let object = value.as_object().ok_or(eyre!("json should be an object"))?;
+
+    let Some(dependencies) = object.get("dependencies") else {
+        return Ok(BTreeMap::new());
+    };
+
+    Ok(dependencies
+        .as_object()
+        .ok_or(eyre!("the dependencies value should be an object"))?
+        .iter()
+        .map(|(name, dependency)| {
+            // `list` outputs contain objects whereas `package.json` files contain version ranges
+            let version = dependency["version"]
+                .as_str()
+                .or_else(|| dependency.as_str())
+                .map(String::from);
+
+            (name.clone(), version)
+        })
+        .collect())
+}
+
+/// Returns the installed version of a package from its `package.json` in the given
+/// `node_modules` directory.
+pub fn installed_version(node_modules: &Path, package: &str) -> Option<String> {
+    let contents = std::fs::read_to_string(node_modules.join(package).join("package.json")).ok()?;
+    let value: Value = serde_json::from_str(&contents).ok()?;
+
+    value["version"].as_str().map(String::from)
+}
+
+/// Joins a package name and an optional version into a spec such as `typescript@5.4`.
+pub fn package_spec(package: &str, version: Option<&String>) -> String {
+    match version {
+        Some(version) => format!("{package}@{version}"),
+        None => package.to_string(),
+    }
+}
+
+/// The format of the config file in which a package manager stores its scoped registries.
+#[derive(Debug, Clone, Copy)]
+pub enum ScopeConfigFormat {
+    /// `@scope:registry=https://registry.example.com`
+    Npmrc,
+    /// `"@scope:registry" "https://registry.example.com"`
+    Yarnrc,
+    /// `[install.scopes]` table containing `"@scope" = "https://registry.example.com"`
+    Bunfig,
+}
+
+/// Validates repos which map a scope to a registry and returns them as a map from the scope to
+/// the registry.
+pub fn scope_registries<'a>(
+    repos: impl Iterator<Item = (&'a String, &'a Option<String>)>,
+) -> Result<BTreeMap<String, String>> {
+    repos
+        .map(|(scope, registry)| {
+            if !scope.starts_with('@') || scope.len() == 1 {
+                return Err(eyre!(
+                    "invalid repo name: {scope:?}, repos should be package scopes such as \"@corp\""
+                ));
+            }
+
+            let registry = registry
+                .clone()
+                .ok_or(eyre!("repo {scope:?} is missing the registry option"))?;
+
+            Ok((scope.clone(), registry))
+        })
+        .collect()
+}
+
+pub fn read_scope_registries(
+    path: &Path,
+    format: ScopeConfigFormat,
+) -> Result<BTreeMap<String, String>> {
+    if !path.is_file() {
+        return Ok(BTreeMap::new());
+    }
+
+    let contents =
+        std::fs::read_to_string(path).wrap_err(eyre!("reading config file: {}", path.display()))?;
+
+    match format {
+        ScopeConfigFormat::Npmrc | ScopeConfigFormat::Yarnrc => Ok(contents
+            .lines()
+            .filter_map(|line| parse_scope_line(line, format))
+            .collect()),
+        ScopeConfigFormat::Bunfig => {
+            let document = parse_bunfig(&contents, path)?;
+
+            Ok(document
+                .get("install")
+                .and_then(|x| x.get("scopes"))
+                .and_then(Item::as_table_like)
+                .into_iter()
+                .flat_map(|x| x.iter())
+                .filter_map(|(scope, registry)| {
+                    // a scope can either be a url or a table containing the url and credentials
+                    let registry = registry
+                        .as_str()
+                        .or_else(|| registry.get("url").and_then(Item::as_str))?;
+
+                    Some((normalize_scope(scope), registry.to_string()))
+                })
+                .collect())
+        }
+    }
+}
+
+pub fn add_scope_registries(
+    path: &Path,
+    format: ScopeConfigFormat,
+    registries: &BTreeMap<String, String>,
+) -> Result<()> {
+    if registries.is_empty() {
+        return Ok(());
+    }
+
+    let contents = read_optional(path)?;
+
+    let contents = match format {
+        ScopeConfigFormat::Npmrc | ScopeConfigFormat::Yarnrc => {
+            let mut lines = contents
+                .lines()
+                .filter(|line| {
+                    parse_scope_line(line, format)
+                        .is_none_or(|(scope, _)| !registries.contains_key(&scope))
+                })
+                .map(String::from)
+                .collect::<Vec<_>>();
+
+            for (scope, registry) in registries {
+                lines.push(match format {
+                    ScopeConfigFormat::Yarnrc => format!("\"{scope}:registry\" \"{registry}\""),
+                    _ => format!("{scope}:registry={registry}"),
+                });
+            }
+
+            lines.into_iter().map(|x| x + "\n").collect()
+        }
+        ScopeConfigFormat::Bunfig => {
+            let mut document = parse_bunfig(&contents, path)?;
+
+            let scopes = bunfig_scopes(&mut document)?;
+            for (scope, registry) in registries {
+                scopes.remove(scope.trim_start_matches('@'));
+                scopes.insert(scope, value(registry));
+            }
+
+            document.to_string()
+        }
+    };
+
+    write(path, &contents)
+}
+
+pub fn remove_scope_registries(
+    path: &Path,
+    format: ScopeConfigFormat,
+    scopes: &BTreeSet<String>,
+) -> Result<()> {
+    if scopes.is_empty() || !path.is_file() {
+        return Ok(());
+    }
+
+    let contents = read_optional(path)?;
+
+    let contents = match format {
+        ScopeConfigFormat::Npmrc | ScopeConfigFormat::Yarnrc => contents
+            .lines()
+            .filter(|line| {
+                parse_scope_line(line, format).is_none_or(|(scope, _)| !scopes.contains(&scope))
+            })
+            .map(|x| x.to_string() + "\n")
+            .collect(),
+        ScopeConfigFormat::Bunfig => {
+            let mut document = parse_bunfig(&contents, path)?;
+
+            let table = bunfig_scopes(&mut document)?;
+            for scope in scopes {
+                table.remove(scope);
+                table.remove(scope.trim_start_matches('@'));
+            }
+
+            document.to_string()
+        }
+    };
+
+    write(path, &contents)
+}
+
+fn parse_scope_line(line: &str, format: ScopeConfigFormat) -> Option<(String, String)> {
+    let (key, registry) = match format {
+        ScopeConfigFormat::Yarnrc => line.trim().split_once(char::is_whitespace)?,
+        _ => line.split_once('=')?,
+    };
+
+    let unquote = |x: &str| x.trim().trim_matches('"').to_string();
+
+    let scope = unquote(key).strip_suffix(":registry")?.to_string();
+
+    scope.starts_with('@').then(|| (scope, unquote(registry)))
+}
+
+/// bun allows scopes to be given with or without their leading `@`.
+fn normalize_scope(scope: &str) -> String {
+    format!("@{}", scope.trim_start_matches('@'))
+}
+
+fn parse_bunfig(contents: &str, path: &Path) -> Result<DocumentMut> {
+    contents
+        .parse()
+        .wrap_err(eyre!("parsing bunfig: {}", path.display()))
+}
+
+fn bunfig_scopes(document: &mut DocumentMut) -> Result<&mut Table> {
+    document
+        .entry("install")
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()
+        .ok_or(eyre!("the bunfig install config should be a table"))?
+        .entry("scopes")
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()
+        .ok_or(eyre!("the bunfig install.scopes config should be a table"))
+}
+
+fn read_optional(path: &Path) -> Result<String> {
+    if path.is_file() {
+        std::fs::read_to_string(path).wrap_err(eyre!("reading config file: {}", path.display()))
+    } else {
+        Ok(String::new())
+    }
+}
+
+fn write(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(path, contents).wrap_err(eyre!("writing config file: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_dependency_versions() {
+        // `npm list --global --json`
+        let npm = indoc::indoc! {r#"
+            {
+              "name": "lib",
+              "dependencies": {
+                "@angular/cli": {
+                  "version": "18.2.1",
+                  "overridden": false
+                },
+                "typescript": {
+                  "version": "5.4.5",
+                  "overridden": false
+                }
+              }
+            }
+        "#};
+        // the first element of `pnpm list --global --json`
+        let pnpm = indoc::indoc! {r#"
+            {
+              "path": "/home/user/.local/share/pnpm/global/5",
+              "private": false,
+              "dependencies": {
+                "typescript": {
+                  "from": "typescript",
+                  "version": "5.4.5",
+                  "resolved": "https://registry.npmjs.org/typescript/-/typescript-5.4.5.tgz",
+                  "path": "/home/user/.local/share/pnpm/global/5/node_modules/typescript"
+                }
+              }
+            }
+        "#};
+        // the global `package.json` of yarn and bun
+        let package_json = indoc::indoc! {r#"
+            {
+              "dependencies": {
+                "@angular/cli": "^18.2.1",
+                "typescript": "5.4"
+              }
+            }
+        "#};
+
+        let extract = |x: &str| extract_dependencies(&serde_json::from_str(x).unwrap()).unwrap();
+        let expected = |x: &[(&str, &str)]| {
+            x.iter()
+                .map(|(name, version)| (name.to_string(), Some(version.to_string())))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        assert_eq!(
+            extract(npm),
+            expected(&[("@angular/cli", "18.2.1"), ("typescript", "5.4.5")])
+        );
+        assert_eq!(extract(pnpm), expected(&[("typescript", "5.4.5")]));
+        assert_eq!(
+            extract(package_json),
+            expected(&[("@angular/cli", "^18.2.1"), ("typescript", "5.4")])
+        );
+        // npm omits the dependencies when there are no global packages
+        assert_eq!(extract(r#"{"name": "lib"}"#), BTreeMap::new());
+        assert!(extract_dependencies(&serde_json::json!([])).is_err());
+    }
+}
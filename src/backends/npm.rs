@@ -1,4 +1,10 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use crate::backends::node::{
+    ScopeConfigFormat, add_scope_registries, extract_dependencies, package_spec,
+    read_scope_registries, remove_scope_registries, scope_registries,
+};
 
 use crate::cmd::{run_command, run_command_for_stdout};
 use crate::prelude::*;
@@ -16,11 +22,15 @@ pub struct NpmConfig {}
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct NpmPackageOptions {}
+pub struct NpmPackageOptions {
+    pub version: Option<String>,
+}
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct NpmRepoOptions {}
+pub struct NpmRepoOptions {
+    pub registry: Option<String>,
+}
 
 impl Backend for Npm {
     type Config = NpmConfig;
@@ -53,21 +63,10 @@ impl Backend for Npm {
         )?;
 
         let value: Value = serde_json::from_str(&stdout)?;
-        let object = value.as_object().ok_or(eyre!("json should be an object"))?;
-
-        if !object.contains_key("dependencies") {
-            return Ok(BTreeMap::new());
-        }
 
-        let names = object["dependencies"]
-            .as_object()
-            .ok_or(eyre!("the dependencies value should be an object"))?
-            .iter()
-            .map(|(name, _)| name.clone());
-
-        Ok(names
+        Ok(extract_dependencies(&value)?
             .into_iter()
-            .map(|name| (name, NpmPackageOptions {}))
+            .map(|(name, version)| (name, NpmPackageOptions { version }))
             .collect())
     }
 
@@ -80,7 +79,12 @@ impl Backend for Npm {
             run_command(
                 ["npm", "install", "--global"]
                     .into_iter()
-                    .chain(packages.keys().map(String::as_str)),
+                    .map(String::from)
+                    .chain(
+                        packages.iter().map(|(package, options)| {
+                            package_spec(package, options.version.as_ref())
+                        }),
+                    ),
                 Perms::Same,
             )?;
         }
@@ -124,8 +128,24 @@ impl Backend for Npm {
         })
     }
 
-    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
-        Ok(BTreeMap::new())
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        Ok(
+            read_scope_registries(&scope_config_path()?, ScopeConfigFormat::Npmrc)?
+                .into_iter()
+                .map(|(scope, registry)| {
+                    (
+                        scope,
+                        Self::RepoOptions {
+                            registry: Some(registry),
+                        },
+                    )
+                })
+                .collect(),
+        )
     }
 
     fn add_repos(
@@ -134,21 +154,40 @@ impl Backend for Npm {
         _: &Self::Config,
     ) -> Result<()> {
         if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+            return Ok(());
         }
+
+        add_scope_registries(
+            &scope_config_path()?,
+            ScopeConfigFormat::Npmrc,
+            &scope_registries(
+                repos
+                    .iter()
+                    .map(|(scope, options)| (scope, &options.registry)),
+            )?,
+        )
     }
 
     fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
         if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+            return Ok(());
         }
+
+        remove_scope_registries(&scope_config_path()?, ScopeConfigFormat::Npmrc, repos)
     }
 
     fn version(_: &Self::Config) -> Result<String> {
         run_command_for_stdout(["npm", "--version"], Perms::Same, StdErr::Show)
     }
 }
+
+/// Returns the path of the user-level `.npmrc` file.
+fn scope_config_path() -> Result<PathBuf> {
+    let path = run_command_for_stdout(
+        ["npm", "config", "get", "userconfig"],
+        Perms::Same,
+        StdErr::Show,
+    )?;
+
+    Ok(PathBuf::from(path.trim()))
+}
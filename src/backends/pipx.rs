@@ -3,6 +3,7 @@ use std::collections::BTreeSet;
 
 use color_eyre::Result;
 use color_eyre::eyre::eyre;
+use itertools::Itertools;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value;
@@ -20,7 +21,16 @@ pub struct PipxConfig {}
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct PipxPackageOptions {}
+pub struct PipxPackageOptions {
+    /// The requirement specifier or url to install instead of the package name, such as
+    /// `black==24.1` or `git+https://github.com/psf/black`.
+    pub spec: Option<String>,
+    pub python: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pip_args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inject: Vec<String>,
+}
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -50,15 +60,9 @@ impl Backend for Pipx {
             return Ok(BTreeMap::new());
         }
 
-        let names = extract_package_names(&run_command_for_stdout(
-            ["pipx", "list", "--json"],
-            Perms::Same,
-            StdErr::Hide,
-        )?)?;
-
-        Ok(names
+        Ok(get_venvs()?
             .into_iter()
-            .map(|x| (x, Self::PackageOptions {}))
+            .map(|(name, venv)| (name, venv.options))
             .collect())
     }
 
@@ -67,13 +71,47 @@ impl Backend for Pipx {
         _: bool,
         _: &Self::Config,
     ) -> Result<()> {
-        if !packages.is_empty() {
+        for (package, options) in packages {
             run_command(
-                ["pipx", "install"]
+                ["pipx".to_string(), "install".to_string()]
                     .into_iter()
-                    .chain(packages.keys().map(String::as_str)),
+                    .chain(
+                        options
+                            .python
+                            .iter()
+                            .flat_map(|x| ["--python".to_string(), x.clone()]),
+                    )
+                    .chain(
+                        (!options.pip_args.is_empty())
+                            .then(|| format!("--pip-args={}", options.pip_args.join(" "))),
+                    )
+                    .chain([options.spec.clone().unwrap_or_else(|| package.clone())]),
                 Perms::Same,
             )?;
+
+            inject(package, &options.inject)?;
+        }
+
+        Ok(())
+    }
+
+    fn package_option_changes(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        config: &Self::Config,
+    ) -> Result<Vec<String>> {
+        Ok(get_missing_injections(packages, config)?
+            .into_iter()
+            .map(|(package, missing)| format!("inject {} into {package}", missing.join(" ")))
+            .collect())
+    }
+
+    fn sync_package_options(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        for (package, missing) in get_missing_injections(packages, config)? {
+            inject(package, &missing)?;
         }
 
         Ok(())
@@ -137,15 +175,126 @@ impl Backend for Pipx {
     }
 }
 
-fn extract_package_names(stdout: &str) -> Result<BTreeSet<String>> {
+/// Returns the `inject` packages which are missing from each of the given installed packages.
+fn get_missing_injections<'a>(
+    packages: &'a BTreeMap<String, PipxPackageOptions>,
+    config: &PipxConfig,
+) -> Result<BTreeMap<&'a String, Vec<String>>> {
+    if packages.values().all(|x| x.inject.is_empty()) || Pipx::version(config).is_err() {
+        return Ok(BTreeMap::new());
+    }
+
+    let venvs = get_venvs()?;
+
+    let mut missing_injections = BTreeMap::new();
+    for (package, options) in packages {
+        let Some(venv) = venvs.get(package) else {
+            continue;
+        };
+
+        let missing = options
+            .inject
+            .iter()
+            .filter(|x| !venv.injected_names.contains(*x) && !venv.options.inject.contains(x))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            missing_injections.insert(package, missing);
+        }
+    }
+
+    Ok(missing_injections)
+}
+
+fn inject(package: &str, dependencies: &[String]) -> Result<()> {
+    if dependencies.is_empty() {
+        return Ok(());
+    }
+
+    run_command(
+        ["pipx", "inject", package]
+            .into_iter()
+            .chain(dependencies.iter().map(String::as_str)),
+        Perms::Same,
+    )
+}
+
+struct Venv {
+    options: PipxPackageOptions,
+    /// The names of the injected packages as opposed to their specs which are stored in
+    /// `options.inject`.
+    injected_names: BTreeSet<String>,
+}
+
+fn get_venvs() -> Result<BTreeMap<String, Venv>> {
+    extract_venvs(&run_command_for_stdout(
+        ["pipx", "list", "--json"],
+        Perms::Same,
+        StdErr::Hide,
+    )?)
+}
+
+fn extract_venvs(stdout: &str) -> Result<BTreeMap<String, Venv>> {
     let value: Value = serde_json::from_str(stdout)?;
 
-    let result = value["venvs"]
+    value["venvs"]
         .as_object()
         .ok_or(eyre!("getting inner json object"))?
         .iter()
-        .map(|(name, _)| name.clone())
-        .collect();
+        .map(|(name, venv)| {
+            let metadata = &venv["metadata"];
+            let main_package = &metadata["main_package"];
+
+            // the spec is the same as the package name unless a version or url was given
+            let spec = main_package["package_or_url"]
+                .as_str()
+                .filter(|x| x != name)
+                .map(String::from);
+
+            // pipx reports the full version such as "Python 3.11.4" but only the minor version
+            // is useful for reinstalling
+            let python = metadata["python_version"]
+                .as_str()
+                .map(|x| x.trim_start_matches("Python ").split('.').take(2).join("."));
+
+            let pip_args = main_package["pip_args"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect();
+
+            let injected = metadata["injected_packages"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+
+            let inject = injected
+                .iter()
+                .map(|(name, package)| {
+                    package["package_or_url"]
+                        .as_str()
+                        .map_or_else(|| (*name).clone(), String::from)
+                })
+                .collect();
+
+            let injected_names = injected.iter().map(|(name, _)| (*name).clone()).collect();
 
-    Ok(result)
+            Ok((
+                name.clone(),
+                Venv {
+                    options: PipxPackageOptions {
+                        spec,
+                        python,
+                        pip_args,
+                        inject,
+                    },
+                    injected_names,
+                },
+            ))
+        })
+        .collect()
 }
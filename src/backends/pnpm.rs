@@ -1,4 +1,10 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use crate::backends::node::{
+    ScopeConfigFormat, add_scope_registries, extract_dependencies, package_spec,
+    read_scope_registries, remove_scope_registries, scope_registries,
+};
 
 use color_eyre::Result;
 use color_eyre::eyre::eyre;
@@ -17,11 +23,15 @@ pub struct PnpmConfig {}
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct PnpmPackageOptions {}
+pub struct PnpmPackageOptions {
+    pub version: Option<String>,
+}
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct PnpmRepoOptions {}
+pub struct PnpmRepoOptions {
+    pub registry: Option<String>,
+}
 
 impl Backend for Pnpm {
     type Config = PnpmConfig;
@@ -54,23 +64,17 @@ impl Backend for Pnpm {
         )?;
 
         let value: Value = serde_json::from_str(&stdout)?;
-        let first_object = value.as_array().ok_or(eyre!("json should be an array"))?[0]
-            .as_object()
-            .ok_or(eyre!("json should be an object"))?;
-
-        if !first_object.contains_key("dependencies") {
+        let Some(first_value) = value
+            .as_array()
+            .ok_or(eyre!("json should be an array"))?
+            .first()
+        else {
             return Ok(BTreeMap::new());
-        }
-
-        let names = first_object["dependencies"]
-            .as_object()
-            .ok_or(eyre!("the dependencies value should be an object"))?
-            .iter()
-            .map(|(name, _)| name.clone());
+        };
 
-        Ok(names
+        Ok(extract_dependencies(first_value)?
             .into_iter()
-            .map(|name| (name, PnpmPackageOptions {}))
+            .map(|(name, version)| (name, PnpmPackageOptions { version }))
             .collect())
     }
 
@@ -83,7 +87,12 @@ impl Backend for Pnpm {
             run_command(
                 ["pnpm", "install", "--global"]
                     .into_iter()
-                    .chain(packages.keys().map(String::as_str)),
+                    .map(String::from)
+                    .chain(
+                        packages.iter().map(|(package, options)| {
+                            package_spec(package, options.version.as_ref())
+                        }),
+                    ),
                 Perms::Same,
             )?;
         }
@@ -127,8 +136,24 @@ impl Backend for Pnpm {
         })
     }
 
-    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
-        Ok(BTreeMap::new())
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        Ok(
+            read_scope_registries(&scope_config_path()?, ScopeConfigFormat::Npmrc)?
+                .into_iter()
+                .map(|(scope, registry)| {
+                    (
+                        scope,
+                        Self::RepoOptions {
+                            registry: Some(registry),
+                        },
+                    )
+                })
+                .collect(),
+        )
     }
 
     fn add_repos(
@@ -137,21 +162,39 @@ impl Backend for Pnpm {
         _: &Self::Config,
     ) -> Result<()> {
         if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+            return Ok(());
         }
+
+        add_scope_registries(
+            &scope_config_path()?,
+            ScopeConfigFormat::Npmrc,
+            &scope_registries(
+                repos
+                    .iter()
+                    .map(|(scope, options)| (scope, &options.registry)),
+            )?,
+        )
     }
 
     fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
         if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+            return Ok(());
         }
+
+        remove_scope_registries(&scope_config_path()?, ScopeConfigFormat::Npmrc, repos)
     }
 
     fn version(_: &Self::Config) -> Result<String> {
         run_command_for_stdout(["pnpm", "--version"], Perms::Same, StdErr::Show)
     }
 }
+
+/// Returns the path of pnpm's global `rc` file which is in the same format as a `.npmrc` file.
+fn scope_config_path() -> Result<PathBuf> {
+    if cfg!(windows) {
+        dirs::data_local_dir().map(|x| x.join("pnpm/config/rc"))
+    } else {
+        dirs::preference_dir().map(|x| x.join("pnpm/rc"))
+    }
+    .ok_or(eyre!("getting the pnpm global config directory"))
+}
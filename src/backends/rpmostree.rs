@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use color_eyre::Result;
+use color_eyre::eyre::{ContextCompat, eyre};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::cmd::{run_command, run_command_for_stdout};
+use crate::prelude::*;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
+pub struct RpmOstree;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RpmOstreeConfig {
+    /// Whether to pass `--apply-live` when installing packages so they can be used without
+    /// rebooting.
+    #[serde(default)]
+    pub apply_live: bool,
+    /// Whether to pass `--idempotent` so that already requested packages aren't an error.
+    #[serde(default)]
+    pub idempotent: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RpmOstreePackageOptions {}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RpmOstreeRepoOptions {}
+
+impl Backend for RpmOstree {
+    type Config = RpmOstreeConfig;
+    type PackageOptions = RpmOstreePackageOptions;
+    type RepoOptions = RpmOstreeRepoOptions;
+
+    fn invalid_package_help_text() -> String {
+        String::new()
+    }
+
+    fn is_valid_package_name(_: &str) -> Option<bool> {
+        None
+    }
+
+    fn get_all_packages(_: &Self::Config) -> Result<BTreeSet<String>> {
+        Err(eyre!("unimplemented"))
+    }
+
+    fn get_installed_packages(
+        config: &Self::Config,
+    ) -> Result<BTreeMap<String, Self::PackageOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        // the first deployment is the one that will be booted next, so packages requested in a
+        // deployment which is still pending a reboot are already counted as installed
+        let status = get_status()?;
+        let deployment = status["deployments"]
+            .as_array()
+            .and_then(|x| x.first())
+            .wrap_err("rpm-ostree status should contain at least one deployment")?;
+
+        Ok(["requested-packages", "requested-local-packages"]
+            .into_iter()
+            .filter_map(|key| deployment[key].as_array())
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(|x| (x.to_string(), Self::PackageOptions {}))
+            .collect())
+    }
+
+    fn install_packages(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        run_command(
+            ["rpm-ostree", "install"]
+                .into_iter()
+                .chain(config.apply_live.then_some("--apply-live"))
+                .chain(config.idempotent.then_some("--idempotent"))
+                .chain(packages.keys().map(String::as_str)),
+            Perms::Sudo,
+        )?;
+
+        // `--apply-live` makes the packages usable straight away so there's no need to reboot
+        if config.apply_live {
+            return Ok(());
+        }
+
+        warn_if_reboot_required(config)
+    }
+
+    fn uninstall_packages(
+        packages: &BTreeSet<String>,
+        _: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        run_command(
+            ["rpm-ostree", "uninstall"]
+                .into_iter()
+                .chain(config.idempotent.then_some("--idempotent"))
+                .chain(packages.iter().map(String::as_str)),
+            Perms::Sudo,
+        )?;
+
+        warn_if_reboot_required(config)
+    }
+
+    fn package_option_changes(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        config: &Self::Config,
+    ) -> Result<Vec<String>> {
+        if config.apply_live || Self::version(config).is_err() {
+            return Ok(Vec::new());
+        }
+
+        // there are no package options, but this is where the user is told about the reboot
+        // before confirming the install
+        let installed = Self::get_installed_packages(config)?;
+        let missing = packages
+            .keys()
+            .filter(|x| !installed.contains_key(*x))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        Ok(vec![format!(
+            "installing {} stages a new deployment which requires a reboot, set `apply_live = \
+             true` to use them without rebooting",
+            missing.join(", ")
+        )])
+    }
+
+    fn update_packages(
+        packages: &BTreeSet<String>,
+        no_confirm: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        log::warn!(
+            "rpm-ostree can only upgrade the whole deployment so all packages will be updated"
+        );
+
+        Self::update_all_packages(no_confirm, config)
+    }
+
+    fn update_all_packages(_: bool, config: &Self::Config) -> Result<()> {
+        run_command(["rpm-ostree", "upgrade"], Perms::Sudo)?;
+
+        warn_if_reboot_required(config)
+    }
+
+    fn clean_cache(config: &Self::Config) -> Result<()> {
+        Self::version(config).map_or(Ok(()), |_| {
+            run_command(["rpm-ostree", "cleanup", "--repomd"], Perms::Sudo)
+        })
+    }
+
+    fn refresh(_: &Self::Config) -> Result<()> {
+        run_command(["rpm-ostree", "refresh-md"], Perms::Sudo)
+    }
+
+    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        Ok(BTreeMap::new())
+    }
+
+    fn add_repos(
+        repos: &BTreeMap<String, Self::RepoOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        if repos.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("unimplemented"))
+        }
+    }
+
+    fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        if repos.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("unimplemented"))
+        }
+    }
+
+    fn version(_: &Self::Config) -> Result<String> {
+        run_command_for_stdout(["rpm-ostree", "--version"], Perms::Same, StdErr::Show)
+    }
+}
+
+fn get_status() -> Result<Value> {
+    let stdout = run_command_for_stdout(
+        ["rpm-ostree", "status", "--json"],
+        Perms::Same,
+        StdErr::Show,
+    )?;
+
+    Ok(serde_json::from_str(&stdout)?)
+}
+
+fn warn_if_reboot_required(config: &RpmOstreeConfig) -> Result<()> {
+    if RpmOstree::version(config).is_err() {
+        return Ok(());
+    }
+
+    let status = get_status()?;
+    let pending = status["deployments"]
+        .as_array()
+        .and_then(|x| x.first())
+        .is_some_and(|x| x["booted"].as_bool() == Some(false));
+
+    if pending {
+        log::warn!(
+            "a new rpm-ostree deployment is pending, reboot to boot into it and finish applying \
+             your layered package changes"
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,391 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use color_eyre::Result;
+use color_eyre::eyre::{ContextCompat, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::cmd::{run_command, run_command_for_stdout};
+use crate::prelude::*;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
+pub struct Rustup;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RustupConfig {
+    /// The toolchain to set as the default toolchain, such as `stable`.
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RustupPackageOptions {
+    /// Components to install for the toolchain such as `rust-analyzer` or `clippy`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<String>,
+    /// Targets to install for the toolchain such as `wasm32-unknown-unknown`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+    /// The profile to install the toolchain with such as `minimal`, only used when the
+    /// toolchain is first installed.
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RustupRepoOptions {}
+
+impl Backend for Rustup {
+    type Config = RustupConfig;
+    type PackageOptions = RustupPackageOptions;
+    type RepoOptions = RustupRepoOptions;
+
+    fn invalid_package_help_text() -> String {
+        String::new()
+    }
+
+    fn is_valid_package_name(_: &str) -> Option<bool> {
+        None
+    }
+
+    fn get_all_packages(_: &Self::Config) -> Result<BTreeSet<String>> {
+        Err(eyre!("unimplemented"))
+    }
+
+    fn get_installed_packages(
+        config: &Self::Config,
+    ) -> Result<BTreeMap<String, Self::PackageOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let host = host_triple()?;
+
+        get_toolchains(&host)?
+            .into_iter()
+            .map(|toolchain| {
+                let components = get_components(&toolchain, &host)?;
+                let targets = get_targets(&toolchain)?;
+
+                Ok((
+                    toolchain,
+                    Self::PackageOptions {
+                        components: components.into_iter().collect(),
+                        targets: targets.into_iter().collect(),
+                        profile: None,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    fn install_packages(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        for (toolchain, options) in packages {
+            run_command(
+                ["rustup", "toolchain", "install", toolchain.as_str()]
+                    .into_iter()
+                    .chain(
+                        options
+                            .profile
+                            .iter()
+                            .flat_map(|x| ["--profile", x.as_str()]),
+                    )
+                    .chain(
+                        options
+                            .components
+                            .iter()
+                            .flat_map(|x| ["--component", x.as_str()]),
+                    )
+                    .chain(
+                        options
+                            .targets
+                            .iter()
+                            .flat_map(|x| ["--target", x.as_str()]),
+                    ),
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn package_option_changes(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        config: &Self::Config,
+    ) -> Result<Vec<String>> {
+        Ok(get_option_changes(packages, config)?
+            .iter()
+            .map(|x| x.join(" "))
+            .collect())
+    }
+
+    fn sync_package_options(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        get_option_changes(packages, config)?
+            .iter()
+            .try_for_each(|cmd| run_command(cmd, Perms::Same))
+    }
+
+    fn uninstall_packages(packages: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        if !packages.is_empty() {
+            run_command(
+                ["rustup", "toolchain", "uninstall"]
+                    .into_iter()
+                    .chain(packages.iter().map(String::as_str)),
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_packages(packages: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        if !packages.is_empty() {
+            run_command(
+                ["rustup", "update"]
+                    .into_iter()
+                    .chain(packages.iter().map(String::as_str)),
+                Perms::Same,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn update_all_packages(_: bool, _: &Self::Config) -> Result<()> {
+        run_command(["rustup", "update"], Perms::Same)
+    }
+
+    fn clean_cache(_: &Self::Config) -> Result<()> {
+        Ok(())
+    }
+
+    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        Ok(BTreeMap::new())
+    }
+
+    fn add_repos(
+        repos: &BTreeMap<String, Self::RepoOptions>,
+        _: bool,
+        _: &Self::Config,
+    ) -> Result<()> {
+        if repos.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("unimplemented"))
+        }
+    }
+
+    fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
+        if repos.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("unimplemented"))
+        }
+    }
+
+    fn version(_: &Self::Config) -> Result<String> {
+        run_command_for_stdout(["rustup", "--version"], Perms::Same, StdErr::Hide)
+    }
+}
+
+/// Returns the `rustup` commands needed to add the missing components and targets of the installed
+/// toolchains and to switch the default toolchain. Toolchains which aren't installed yet are
+/// skipped since they are installed along with their components and targets.
+fn get_option_changes(
+    packages: &BTreeMap<String, RustupPackageOptions>,
+    config: &RustupConfig,
+) -> Result<Vec<Vec<String>>> {
+    if Rustup::version(config).is_err() {
+        return Ok(Vec::new());
+    }
+
+    let host = host_triple()?;
+    let toolchains = get_toolchains(&host)?;
+
+    let add = |kind: &str, toolchain: &str, missing: Vec<&String>| {
+        (!missing.is_empty()).then(|| {
+            ["rustup", kind, "add", "--toolchain", toolchain]
+                .into_iter()
+                .map(String::from)
+                .chain(missing.into_iter().cloned())
+                .collect::<Vec<_>>()
+        })
+    };
+
+    let mut commands = Vec::new();
+
+    // components and targets which aren't in your group files are left alone since toolchain
+    // profiles install their own components
+    for (toolchain, options) in packages
+        .iter()
+        .filter(|(toolchain, _)| toolchains.contains(*toolchain))
+    {
+        let components = get_components(toolchain, &host)?;
+        commands.extend(add(
+            "component",
+            toolchain,
+            options
+                .components
+                .iter()
+                .filter(|x| !components.contains(*x))
+                .collect(),
+        ));
+
+        let targets = get_targets(toolchain)?;
+        commands.extend(add(
+            "target",
+            toolchain,
+            options
+                .targets
+                .iter()
+                .filter(|x| !targets.contains(*x))
+                .collect(),
+        ));
+    }
+
+    if let Some(default) = &config.default {
+        let stdout = run_command_for_stdout(["rustup", "default"], Perms::Same, StdErr::Hide)
+            .unwrap_or_default();
+
+        // example output:
+        // stable-x86_64-unknown-linux-gnu (default)
+        let current = stdout
+            .split_whitespace()
+            .next()
+            .map(|x| strip_host(x, &host));
+
+        if current != Some(default.as_str()) {
+            commands.push(vec![
+                "rustup".to_string(),
+                "default".to_string(),
+                default.clone(),
+            ]);
+        }
+    }
+
+    Ok(commands)
+}
+
+fn host_triple() -> Result<String> {
+    let stdout = run_command_for_stdout(["rustup", "show"], Perms::Same, StdErr::Hide)?;
+
+    // example output:
+    // Default host: x86_64-unknown-linux-gnu
+    stdout
+        .lines()
+        .find_map(|x| x.strip_prefix("Default host:"))
+        .map(|x| x.trim().to_string())
+        .wrap_err("missing default host in rustup show output")
+}
+
+/// Strips the host triple from a toolchain or component name such as
+/// `stable-x86_64-unknown-linux-gnu` so that it matches the name used in group files.
+fn strip_host<'a>(name: &'a str, host: &str) -> &'a str {
+    name.strip_suffix(host)
+        .and_then(|x| x.strip_suffix('-'))
+        .unwrap_or(name)
+}
+
+fn get_toolchains(host: &str) -> Result<BTreeSet<String>> {
+    let stdout =
+        run_command_for_stdout(["rustup", "toolchain", "list"], Perms::Same, StdErr::Show)?;
+
+    // example output:
+    // stable-x86_64-unknown-linux-gnu (active, default)
+    // nightly-2026-01-01-x86_64-unknown-linux-gnu
+    Ok(stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        // shown when no toolchains are installed
+        .filter(|x| *x != "no")
+        .map(|x| strip_host(x, host).to_string())
+        .collect())
+}
+
+fn get_components(toolchain: &str, host: &str) -> Result<BTreeSet<String>> {
+    let stdout = run_command_for_stdout(
+        [
+            "rustup",
+            "component",
+            "list",
+            "--installed",
+            "--toolchain",
+            toolchain,
+        ],
+        Perms::Same,
+        StdErr::Show,
+    )?;
+
+    // example output:
+    // clippy-x86_64-unknown-linux-gnu
+    // rust-src
+    // rust-std-wasm32-unknown-unknown
+    Ok(stdout
+        .lines()
+        .map(str::trim)
+        // the standard libraries are managed as targets
+        .filter(|x| !x.is_empty() && !x.starts_with("rust-std-"))
+        .map(|x| strip_host(x, host).to_string())
+        .collect())
+}
+
+fn get_targets(toolchain: &str) -> Result<BTreeSet<String>> {
+    let stdout = run_command_for_stdout(
+        [
+            "rustup",
+            "target",
+            "list",
+            "--installed",
+            "--toolchain",
+            toolchain,
+        ],
+        Perms::Same,
+        StdErr::Show,
+    )?;
+
+    Ok(stdout
+        .lines()
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_host_triples() {
+        let host = "x86_64-unknown-linux-gnu";
+
+        assert_eq!(
+            strip_host("stable-x86_64-unknown-linux-gnu", host),
+            "stable"
+        );
+        assert_eq!(
+            strip_host("nightly-2026-01-01-x86_64-unknown-linux-gnu", host),
+            "nightly-2026-01-01"
+        );
+        assert_eq!(
+            strip_host("clippy-x86_64-unknown-linux-gnu", host),
+            "clippy"
+        );
+        assert_eq!(strip_host("rust-src", host), "rust-src");
+        // a toolchain for another host keeps its triple
+        assert_eq!(
+            strip_host("stable-aarch64-apple-darwin", host),
+            "stable-aarch64-apple-darwin"
+        );
+        assert_eq!(strip_host(host, host), host);
+    }
+}
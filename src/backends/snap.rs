@@ -19,6 +19,9 @@ pub struct SnapConfig {}
 #[serde(deny_unknown_fields)]
 pub struct SnapPackageOptions {
     pub confinement: Option<SnapConfinement>,
+    pub channel: Option<String>,
+    pub revision: Option<u32>,
+    pub hold: Option<bool>,
 }
 
 #[derive(
@@ -33,15 +36,25 @@ pub enum SnapConfinement {
     Jailmode,
 }
 impl SnapConfinement {
-    fn try_from_notes(notes: &str) -> Option<Self> {
-        match notes {
-            "-" => Some(Self::Strict),
-            "classic" => Some(Self::Classic),
-            "dangerous" => Some(Self::Dangerous),
-            "devmode" => Some(Self::Devmode),
-            "jailmode" => Some(Self::Jailmode),
-            _ => None,
-        }
+    /// Derives the confinement from all of the notes of a snap, since notes such as `held` can be
+    /// given alongside the confinement.
+    fn from_notes(notes: &[String]) -> Option<Self> {
+        notes
+            .iter()
+            .find_map(|x| match x.as_str() {
+                "classic" => Some(Self::Classic),
+                "dangerous" => Some(Self::Dangerous),
+                "devmode" => Some(Self::Devmode),
+                "jailmode" => Some(Self::Jailmode),
+                _ => None,
+            })
+            .or_else(|| {
+                // snaps which aren't apps, such as bases, don't have a confinement
+                notes
+                    .iter()
+                    .all(|x| !matches!(x.as_str(), "base" | "core" | "gadget" | "kernel" | "snapd"))
+                    .then_some(Self::Strict)
+            })
     }
 
     fn to_cli_option(&self) -> Option<String> {
@@ -84,19 +97,22 @@ impl Backend for Snap {
             return Ok(BTreeMap::new());
         }
 
-        let output = run_command_for_stdout(["snap", "list"], Perms::Same, StdErr::Show)?;
-
-        // Skip the first line which is the header
-        Ok(output
-            .lines()
-            .skip(1)
-            .filter_map(|line| {
-                let mut fields = line.split_whitespace();
-                fields.next().map(|name|
-                    // skip "Version", "Rev", "Tracking", and "Publisher" fields
-                    (name, fields.nth(4).and_then(SnapConfinement::try_from_notes)))
+        Ok(get_snaps()?
+            .into_iter()
+            .map(|snap| {
+                let held = snap.notes.contains(&"held".to_string());
+
+                (
+                    snap.name,
+                    Self::PackageOptions {
+                        confinement: SnapConfinement::from_notes(&snap.notes),
+                        channel: snap.tracking.filter(|x| x != DEFAULT_CHANNEL),
+                        // a specific revision only stays installed if it is held
+                        revision: snap.revision.filter(|_| held),
+                        hold: held.then_some(true),
+                    },
+                )
             })
-            .map(|(name, confinement)| (name.to_string(), Self::PackageOptions { confinement }))
             .collect())
     }
 
@@ -106,6 +122,35 @@ impl Backend for Snap {
         _: &Self::Config,
     ) -> Result<()> {
         build_snap_install_commands(packages)
+            .iter()
+            .try_for_each(|cmd| run_command(cmd, Perms::Sudo))?;
+
+        for (package, options) in packages {
+            if options.hold == Some(true) {
+                run_command(["snap", "refresh", "--hold", package], Perms::Sudo)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn package_option_changes(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        config: &Self::Config,
+    ) -> Result<Vec<String>> {
+        Ok(get_option_changes(packages, config)?
+            .iter()
+            .map(|x| x.join(" "))
+            .collect())
+    }
+
+    fn sync_package_options(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        // snap refresh doesn't ask for confirmation so there is nothing to pass `no_confirm` to
+        get_option_changes(packages, config)?
             .iter()
             .try_for_each(|cmd| run_command(cmd, Perms::Sudo))
     }
@@ -183,6 +228,121 @@ impl Backend for Snap {
     }
 }
 
+/// The channel snaps track when installed without a `--channel`.
+const DEFAULT_CHANNEL: &str = "latest/stable";
+
+/// A line of `snap list` output.
+struct SnapListEntry {
+    name: String,
+    revision: Option<u32>,
+    tracking: Option<String>,
+    notes: Vec<String>,
+}
+
+fn get_snaps() -> Result<Vec<SnapListEntry>> {
+    let output = run_command_for_stdout(["snap", "list"], Perms::Same, StdErr::Show)?;
+
+    Ok(parse_snap_list(&output))
+}
+
+fn parse_snap_list(output: &str) -> Vec<SnapListEntry> {
+    // example output:
+    // Name    Version    Rev    Tracking       Publisher   Notes
+    // core22  20240111   1122   latest/stable  canonical✓  base
+    // go      1.22.0     10535  1.22/stable    mwhudson    classic,held
+    // Skip the first line which is the header
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+
+            // skip any lines we don't understand such as notes about broken snaps
+            let [name, _version, revision, tracking, _publisher, notes] = fields[..] else {
+                return None;
+            };
+
+            Some(SnapListEntry {
+                name: name.to_string(),
+                // local snaps have revisions such as `x1` which can't be installed from the store
+                revision: revision.parse().ok(),
+                // local snaps aren't tracking a channel
+                tracking: (tracking != "-").then(|| tracking.to_string()),
+                notes: notes.split(',').map(String::from).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Returns the `snap refresh` commands needed to bring the channel, revision and hold of the
+/// given installed snaps in line with their options.
+fn get_option_changes(
+    packages: &BTreeMap<String, SnapPackageOptions>,
+    config: &SnapConfig,
+) -> Result<Vec<Vec<String>>> {
+    if Snap::version(config).is_err() {
+        return Ok(Vec::new());
+    }
+
+    let snaps = get_snaps()?
+        .into_iter()
+        .map(|snap| (snap.name.clone(), snap))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut changes = Vec::new();
+    for (package, options) in packages {
+        let Some(snap) = snaps.get(package) else {
+            continue;
+        };
+
+        let channel = options
+            .channel
+            .as_deref()
+            .map(normalize_channel)
+            .filter(|x| snap.tracking.as_ref() != Some(x));
+        let revision = options.revision.filter(|x| snap.revision != Some(*x));
+
+        if channel.is_some() || revision.is_some() {
+            changes.push(
+                ["snap".to_string(), "refresh".to_string()]
+                    .into_iter()
+                    .chain(channel.map(|x| format!("--channel={x}")))
+                    .chain(revision.map(|x| format!("--revision={x}")))
+                    .chain([package.clone()])
+                    .collect(),
+            );
+        }
+
+        let held = snap.notes.contains(&"held".to_string());
+        let hold_option = match options.hold {
+            Some(true) if !held => Some("--hold"),
+            Some(false) if held => Some("--unhold"),
+            _ => None,
+        };
+        if let Some(hold_option) = hold_option {
+            changes.push(
+                ["snap", "refresh", hold_option, package]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+            );
+        }
+    }
+
+    Ok(changes)
+}
+
+/// Converts a channel into the `track/risk` form that snap displays in its tracking column.
+fn normalize_channel(channel: &str) -> String {
+    if channel.contains('/') {
+        channel.to_string()
+    } else if matches!(channel, "stable" | "candidate" | "beta" | "edge") {
+        format!("latest/{channel}")
+    } else {
+        format!("{channel}/stable")
+    }
+}
+
 fn build_snap_install_commands(
     packages: &BTreeMap<String, SnapPackageOptions>,
 ) -> Vec<Vec<String>> {
@@ -200,7 +360,7 @@ fn build_snap_install_commands(
         .into_group_map()
         .into_iter()
         .sorted()
-        .map(|(confinement, packages_confined)| atomize_not_strict(confinement, packages_confined))
+        .map(|(confinement, packages_confined)| atomize(confinement, packages_confined))
         .flat_map(Vec::into_iter)
         .map(|(confinement, packages_confined)| {
             build_snap_install_command(confinement, packages_confined)
@@ -208,30 +368,107 @@ fn build_snap_install_commands(
         .collect()
 }
 
-fn atomize_not_strict<'a>(
+/// snap only allows installing a single snap at a time when a non-strict confinement, a channel
+/// or a revision is given.
+fn atomize<'a>(
     confinement: &'a SnapConfinement,
     packages_confined: Vec<(&'a String, &'a SnapPackageOptions)>,
 ) -> Vec<(
     &'a SnapConfinement,
     Vec<(&'a String, &'a SnapPackageOptions)>,
 )> {
-    match confinement.to_cli_option() {
-        Some(_) => packages_confined
-            .into_iter()
-            .map(|name2options| (confinement, vec![name2options]))
-            .collect(),
-        None => vec![(confinement, packages_confined)],
-    }
+    let (atomic, grouped): (Vec<_>, Vec<_>) =
+        packages_confined.into_iter().partition(|(_, options)| {
+            confinement.to_cli_option().is_some()
+                || options.channel.is_some()
+                || options.revision.is_some()
+        });
+
+    atomic
+        .into_iter()
+        .map(|name2options| (confinement, vec![name2options]))
+        .chain((!grouped.is_empty()).then_some((confinement, grouped)))
+        .collect()
 }
 
 fn build_snap_install_command<'a>(
     confinement: &'a SnapConfinement,
     packages_confined: Vec<(&'a String, &'a SnapPackageOptions)>,
 ) -> Vec<String> {
+    let (names, options): (Vec<_>, Vec<_>) = packages_confined.into_iter().unzip();
+
     ["snap", "install"]
         .into_iter()
         .map(String::from)
         .chain(confinement.to_cli_option())
-        .chain(packages_confined.into_iter().map(|(name, _)| name.clone()))
+        .chain(
+            options
+                .iter()
+                .filter_map(|x| x.channel.as_ref())
+                .map(|x| format!("--channel={x}")),
+        )
+        .chain(
+            options
+                .iter()
+                .filter_map(|x| x.revision)
+                .map(|x| format!("--revision={x}")),
+        )
+        .chain(names.into_iter().cloned())
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_snap_list_skips_unexpected_lines() {
+        // output of `snap list` with a broken snap
+        let output = indoc::indoc! {"
+            Name    Version    Rev    Tracking       Publisher   Notes
+            core22  20240111   1122   latest/stable  canonical✓  base
+            firefox 122.0-2    3728   latest/stable  mozilla✓    -
+            go      1.22.0     10535  1.22/stable    mwhudson    classic,held
+            hello   2.10       x1     -              -           -
+            broken  unset      x2     -              -           -  broken
+        "};
+
+        let snaps = parse_snap_list(output);
+
+        assert_eq!(
+            snaps.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(),
+            ["core22", "firefox", "go", "hello"]
+        );
+        assert_eq!(snaps[2].revision, Some(10535));
+        assert_eq!(snaps[2].tracking.as_deref(), Some("1.22/stable"));
+        assert_eq!(snaps[2].notes, ["classic", "held"]);
+        assert_eq!(snaps[3].revision, None);
+        assert_eq!(snaps[3].tracking, None);
+    }
+
+    #[test]
+    fn confinement_from_notes() {
+        let notes = |x: &str| x.split(',').map(String::from).collect::<Vec<_>>();
+
+        assert_eq!(
+            SnapConfinement::from_notes(&notes("-")),
+            Some(SnapConfinement::Strict)
+        );
+        assert_eq!(
+            SnapConfinement::from_notes(&notes("held")),
+            Some(SnapConfinement::Strict)
+        );
+        assert_eq!(
+            SnapConfinement::from_notes(&notes("held,classic")),
+            Some(SnapConfinement::Classic)
+        );
+        assert_eq!(SnapConfinement::from_notes(&notes("base")), None);
+    }
+
+    #[test]
+    fn normalize_channels() {
+        assert_eq!(normalize_channel("edge"), "latest/edge");
+        assert_eq!(normalize_channel("1.22"), "1.22/stable");
+        assert_eq!(normalize_channel("1.22/beta"), "1.22/beta");
+    }
+}
@@ -1,10 +1,13 @@
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
+use std::io::ErrorKind::NotFound;
+use std::path::{Path, PathBuf};
 
 use color_eyre::Result;
-use color_eyre::eyre::eyre;
+use color_eyre::eyre::{Context, eyre};
 use serde::Deserialize;
 use serde::Serialize;
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table, value};
 
 use crate::cmd::run_command;
 use crate::cmd::run_command_for_stdout;
@@ -22,11 +25,20 @@ pub struct UvConfig {}
 pub struct UvPackageOptions {
     #[serde(default)]
     python: Option<String>,
+    /// A version specifier such as `==1.5.0` or `>=1,<2`.
+    version: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    with: Vec<String>,
+    index_url: Option<String>,
+    /// A local path to install the package from in editable mode.
+    editable: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct UvRepoOptions {}
+pub struct UvRepoOptions {
+    pub url: Option<String>,
+}
 
 impl Backend for Uv {
     type Config = UvConfig;
@@ -52,18 +64,35 @@ impl Backend for Uv {
             return Ok(BTreeMap::new());
         }
 
-        let names = run_command_for_stdout(
-            ["uv", "tool", "list", "--color", "never"],
-            Perms::Same,
-            StdErr::Hide,
-        )?
-        .lines()
-        .filter(|x| !x.starts_with('-'))
-        .map(|x| x.split(' ').next().unwrap().to_string())
-        .map(|x| (x, Self::PackageOptions { python: None }))
-        .collect();
+        let tool_dir = run_command_for_stdout(["uv", "tool", "dir"], Perms::Same, StdErr::Hide)?;
+
+        let entries = match std::fs::read_dir(tool_dir.trim()) {
+            Ok(entries) => entries,
+            // uv only creates the tool directory when the first tool is installed
+            Err(err) if err.kind() == NotFound => return Ok(BTreeMap::new()),
+            Err(err) => return Err(err).wrap_err("reading uv tool directory"),
+        };
+
+        let mut packages = BTreeMap::new();
+
+        for entry in entries {
+            let path = entry?.path().join("uv-receipt.toml");
+
+            // the tool directory also contains uv's lock files which aren't tools
+            if !path.is_file() {
+                continue;
+            }
+
+            let contents = std::fs::read_to_string(&path)
+                .wrap_err(eyre!("reading uv receipt: {}", path.display()))?;
+
+            let (package, options) = extract_receipt(&contents)
+                .wrap_err(eyre!("parsing uv receipt: {}", path.display()))?;
 
-        Ok(names)
+            packages.insert(package, options);
+        }
+
+        Ok(packages)
     }
 
     fn install_packages(
@@ -72,12 +101,36 @@ impl Backend for Uv {
         _: &Self::Config,
     ) -> Result<()> {
         for (package, options) in packages {
+            let requirement = match &options.editable {
+                Some(path) => vec!["--editable".to_string(), path.clone()],
+                None => vec![format!(
+                    "{package}{}",
+                    options.version.as_deref().unwrap_or_default()
+                )],
+            };
+
             run_command(
-                ["uv", "tool", "install"]
+                ["uv".to_string(), "tool".to_string(), "install".to_string()]
                     .into_iter()
-                    .chain(options.python.is_some().then_some("--python"))
-                    .chain(options.python.as_deref())
-                    .chain([package.as_str()]),
+                    .chain(
+                        options
+                            .python
+                            .iter()
+                            .flat_map(|x| ["--python".to_string(), x.clone()]),
+                    )
+                    .chain(
+                        options
+                            .with
+                            .iter()
+                            .flat_map(|x| ["--with".to_string(), x.clone()]),
+                    )
+                    .chain(
+                        options
+                            .index_url
+                            .iter()
+                            .flat_map(|x| ["--index-url".to_string(), x.clone()]),
+                    )
+                    .chain(requirement),
                 Perms::Same,
             )?;
         }
@@ -119,8 +172,31 @@ impl Backend for Uv {
         Ok(())
     }
 
-    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
-        Ok(BTreeMap::new())
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let Some(path) = user_config_path() else {
+            return Ok(BTreeMap::new());
+        };
+
+        let document = read_user_config(&path)?;
+
+        Ok(document
+            .get("index")
+            .and_then(Item::as_array_of_tables)
+            .into_iter()
+            .flatten()
+            .filter_map(|index| {
+                Some((
+                    index.get("name")?.as_str()?.to_string(),
+                    Self::RepoOptions {
+                        url: index.get("url").and_then(Item::as_str).map(String::from),
+                    },
+                ))
+            })
+            .collect())
     }
 
     fn add_repos(
@@ -129,21 +205,201 @@ impl Backend for Uv {
         _: &Self::Config,
     ) -> Result<()> {
         if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+            return Ok(());
+        }
+
+        let path = user_config_path().ok_or(eyre!("getting the uv user config path"))?;
+        let mut document = read_user_config(&path)?;
+
+        let indexes = document
+            .entry("index")
+            .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()))
+            .as_array_of_tables_mut()
+            .ok_or(eyre!("the uv index config should be an array of tables"))?;
+
+        for (repo, options) in repos {
+            let url = options
+                .url
+                .as_deref()
+                .ok_or(eyre!("uv repo {repo:?} is missing the url option"))?;
+
+            let mut index = Table::new();
+            index.insert("name", value(repo.as_str()));
+            index.insert("url", value(url));
+            indexes.push(index);
         }
+
+        write_user_config(&path, &document)
     }
 
     fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
         if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+            return Ok(());
         }
+
+        let path = user_config_path().ok_or(eyre!("getting the uv user config path"))?;
+        let mut document = read_user_config(&path)?;
+
+        if let Some(indexes) = document
+            .get_mut("index")
+            .and_then(Item::as_array_of_tables_mut)
+        {
+            indexes.retain(|index| {
+                !index
+                    .get("name")
+                    .and_then(Item::as_str)
+                    .is_some_and(|x| repos.contains(x))
+            });
+        }
+
+        write_user_config(&path, &document)
     }
 
     fn version(_: &Self::Config) -> Result<String> {
         run_command_for_stdout(["uv", "--version"], Perms::Same, StdErr::Show)
     }
 }
+
+fn extract_receipt(contents: &str) -> Result<(String, UvPackageOptions)> {
+    let receipt: toml::Table = toml::from_str(contents)?;
+
+    let tool = receipt
+        .get("tool")
+        .and_then(toml::Value::as_table)
+        .ok_or(eyre!("missing 'tool' table"))?;
+
+    let requirements = tool
+        .get("requirements")
+        .and_then(toml::Value::as_array)
+        .ok_or(eyre!("missing 'requirements' array"))?;
+
+    // the first requirement is always the tool itself, the rest are from `--with`
+    let (package, with) = requirements
+        .split_first()
+        .ok_or(eyre!("empty 'requirements' array"))?;
+
+    let name = |x: &toml::Value| -> Result<String> {
+        x.get("name")
+            .and_then(toml::Value::as_str)
+            .map(String::from)
+            .ok_or(eyre!("requirement is missing a name"))
+    };
+    let field =
+        |x: &toml::Value, key: &str| x.get(key).and_then(toml::Value::as_str).map(String::from);
+
+    let with = with
+        .iter()
+        .map(|x| Ok(name(x)? + &field(x, "specifier").unwrap_or_default()))
+        .collect::<Result<_>>()?;
+
+    let options = tool.get("options");
+    let index_url = options
+        .and_then(|x| x.get("index-url"))
+        .and_then(toml::Value::as_str)
+        .map(String::from);
+
+    Ok((
+        name(package)?,
+        UvPackageOptions {
+            python: tool
+                .get("python")
+                .and_then(toml::Value::as_str)
+                .map(String::from),
+            version: field(package, "specifier"),
+            with,
+            index_url,
+            editable: field(package, "editable"),
+        },
+    ))
+}
+
+/// Returns the path of uv's user-level `uv.toml` which is where indexes are configured for
+/// every `uv` command including `uv tool install`.
+fn user_config_path() -> Option<PathBuf> {
+    if cfg!(windows) {
+        dirs::config_dir()
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home::home_dir().map(|x| x.join(".config")))
+    }
+    .map(|x| x.join("uv/uv.toml"))
+}
+
+fn read_user_config(path: &Path) -> Result<DocumentMut> {
+    if !path.is_file() {
+        return Ok(DocumentMut::new());
+    }
+
+    std::fs::read_to_string(path)
+        .wrap_err(eyre!("reading uv config: {}", path.display()))?
+        .parse()
+        .wrap_err(eyre!("parsing uv config: {}", path.display()))
+}
+
+fn write_user_config(path: &Path, document: &DocumentMut) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(path, document.to_string())
+        .wrap_err(eyre!("writing uv config: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_receipts() {
+        // `uv-receipt.toml` of `uv tool install black==24.1.0 --with click --python 3.12
+        // --index-url https://pypi.example.com/simple`
+        let receipt = indoc::indoc! {r#"
+            [tool]
+            requirements = [
+                { name = "black", specifier = "==24.1.0" },
+                { name = "click" },
+            ]
+            python = "3.12"
+            entrypoints = [
+                { name = "black", install-path = "/home/user/.local/bin/black" },
+            ]
+
+            [tool.options]
+            index-url = "https://pypi.example.com/simple"
+        "#};
+
+        assert_eq!(
+            extract_receipt(receipt).unwrap(),
+            (
+                "black".to_string(),
+                UvPackageOptions {
+                    python: Some("3.12".to_string()),
+                    version: Some("==24.1.0".to_string()),
+                    with: vec!["click".to_string()],
+                    index_url: Some("https://pypi.example.com/simple".to_string()),
+                    editable: None,
+                }
+            )
+        );
+
+        // `uv tool install --editable ./tool`
+        let receipt = indoc::indoc! {r#"
+            [tool]
+            requirements = [{ name = "tool", editable = "/home/user/tool" }]
+            entrypoints = []
+        "#};
+
+        assert_eq!(
+            extract_receipt(receipt).unwrap(),
+            (
+                "tool".to_string(),
+                UvPackageOptions {
+                    editable: Some("/home/user/tool".to_string()),
+                    ..Default::default()
+                }
+            )
+        );
+        assert!(extract_receipt("[tool]\nrequirements = []").is_err());
+    }
+}
@@ -5,10 +5,11 @@ use crate::cmd::run_command;
 use crate::cmd::run_command_for_stdout;
 use crate::prelude::*;
 use color_eyre::Result;
-use color_eyre::eyre::eyre;
+use color_eyre::eyre::{Context, eyre};
 use itertools::Itertools;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::Value;
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
 pub struct VsCode;
@@ -34,11 +35,37 @@ impl VsCodeVariant {
             Self::Codium => "codium",
         }
     }
+    /// The name of the folder inside the user's config directory used by this variant.
+    pub fn config_dir_name(self) -> &'static str {
+        match self {
+            Self::Code => "Code",
+            Self::Codium => "VSCodium",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct VsCodePackageOptions {}
+pub struct VsCodePackageOptions {
+    /// The version to install the extension at, read back with `--show-versions`.
+    pub version: Option<String>,
+    /// The profiles to install the extension into, where `Default` is the default profile.
+    /// Defaults to only the default profile.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<String>,
+}
+impl VsCodePackageOptions {
+    fn profiles(&self) -> Vec<&str> {
+        if self.profiles.is_empty() {
+            vec![DEFAULT_PROFILE]
+        } else {
+            self.profiles.iter().map(String::as_str).collect()
+        }
+    }
+}
+
+/// The name VS Code gives the default profile.
+const DEFAULT_PROFILE: &str = "Default";
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -68,16 +95,27 @@ impl Backend for VsCode {
             return Ok(BTreeMap::new());
         }
 
-        let names = run_command_for_stdout(
-            [config.variant.as_command(), "--list-extensions"],
-            Perms::Same,
-            StdErr::Hide,
-        )?
-        .lines()
-        .map(|x| (x.to_string(), Self::PackageOptions {}))
-        .collect();
+        let mut packages = BTreeMap::<String, Self::PackageOptions>::new();
+
+        for profile in get_profiles(config)? {
+            for (package, version) in get_extensions(&profile, config)? {
+                let options = packages.entry(package).or_default();
+
+                // profiles are checked starting with the default profile whose version is
+                // preferred when an extension is at different versions in different profiles
+                options.version.get_or_insert(version);
+                options.profiles.push(profile.clone());
+            }
+        }
+
+        // extensions which are only in the default profile don't need the profiles option
+        for options in packages.values_mut() {
+            if options.profiles == [DEFAULT_PROFILE] {
+                options.profiles.clear();
+            }
+        }
 
-        Ok(names)
+        Ok(packages)
     }
 
     fn install_packages(
@@ -85,10 +123,36 @@ impl Backend for VsCode {
         _: bool,
         config: &Self::Config,
     ) -> Result<()> {
-        for package in packages.keys() {
-            run_command(
-                [config.variant.as_command(), "--install-extension", package],
-                Perms::Same,
+        for (package, options) in packages {
+            for profile in options.profiles() {
+                install_extension(package, options.version.as_deref(), profile, config)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn package_option_changes(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        config: &Self::Config,
+    ) -> Result<Vec<String>> {
+        Ok(get_missing_profiles(packages, config)?
+            .into_iter()
+            .map(|(package, profile)| format!("install {package} into the {profile} profile"))
+            .collect())
+    }
+
+    fn sync_package_options(
+        packages: &BTreeMap<String, Self::PackageOptions>,
+        _: bool,
+        config: &Self::Config,
+    ) -> Result<()> {
+        for (package, profile) in get_missing_profiles(packages, config)? {
+            install_extension(
+                package,
+                packages[package].version.as_deref(),
+                profile,
+                config,
             )?;
         }
 
@@ -100,26 +164,46 @@ impl Backend for VsCode {
         _: bool,
         config: &Self::Config,
     ) -> Result<()> {
+        let installed = Self::get_installed_packages(config)?;
+
         for package in packages {
-            run_command(
-                [
-                    config.variant.as_command(),
-                    "--uninstall-extension",
-                    package,
-                ],
-                Perms::Same,
-            )?;
+            for profile in installed
+                .get(package)
+                .map(|x| x.profiles())
+                .unwrap_or_default()
+            {
+                run_command(
+                    [
+                        config.variant.as_command(),
+                        "--uninstall-extension",
+                        package,
+                    ]
+                    .into_iter()
+                    .chain(profile_args(profile)),
+                    Perms::Same,
+                )?;
+            }
         }
 
         Ok(())
     }
 
     fn update_packages(packages: &BTreeSet<String>, _: bool, config: &Self::Config) -> Result<()> {
+        let installed = Self::get_installed_packages(config)?;
+
         for package in packages {
-            run_command(
-                [config.variant.as_command(), "--install-extension", package],
-                Perms::Same,
-            )?;
+            for profile in installed
+                .get(package)
+                .map(|x| x.profiles())
+                .unwrap_or_default()
+            {
+                run_command(
+                    [config.variant.as_command(), "--install-extension", package]
+                        .into_iter()
+                        .chain(profile_args(profile)),
+                    Perms::Same,
+                )?;
+            }
         }
 
         Ok(())
@@ -171,3 +255,155 @@ impl Backend for VsCode {
         .map(|x| x.lines().join(" "))
     }
 }
+
+fn install_extension(
+    package: &str,
+    version: Option<&str>,
+    profile: &str,
+    config: &VsCodeConfig,
+) -> Result<()> {
+    let extension = match version {
+        Some(version) => format!("{package}@{version}"),
+        None => package.to_string(),
+    };
+
+    run_command(
+        [
+            config.variant.as_command(),
+            "--install-extension",
+            &extension,
+        ]
+        .into_iter()
+        .chain(profile_args(profile)),
+        Perms::Same,
+    )
+}
+
+/// Returns the `--profile` option for the given profile, which isn't needed for the default
+/// profile.
+fn profile_args(profile: &str) -> impl Iterator<Item = &str> {
+    (profile != DEFAULT_PROFILE)
+        .then_some(["--profile", profile])
+        .into_iter()
+        .flatten()
+}
+
+/// Returns the declared profiles that each installed extension is missing from.
+fn get_missing_profiles<'a>(
+    packages: &'a BTreeMap<String, VsCodePackageOptions>,
+    config: &VsCodeConfig,
+) -> Result<Vec<(&'a String, &'a str)>> {
+    let installed = VsCode::get_installed_packages(config)?;
+
+    let mut missing = Vec::new();
+    for (package, options) in packages {
+        // extensions which aren't installed at all are installed into all of their profiles
+        // when they are installed
+        let Some(installed) = installed.get(package) else {
+            continue;
+        };
+
+        let installed_profiles = installed.profiles();
+        for profile in options.profiles() {
+            if !installed_profiles.contains(&profile) {
+                missing.push((package, profile));
+            }
+        }
+    }
+
+    Ok(missing)
+}
+
+/// Returns the installed extensions and their versions for the given profile.
+fn get_extensions(profile: &str, config: &VsCodeConfig) -> Result<BTreeMap<String, String>> {
+    let stdout = run_command_for_stdout(
+        [
+            config.variant.as_command(),
+            "--list-extensions",
+            "--show-versions",
+        ]
+        .into_iter()
+        .chain(profile_args(profile)),
+        Perms::Same,
+        StdErr::Hide,
+    )?;
+
+    stdout
+        .lines()
+        .map(|line| {
+            line.split_once('@')
+                .map(|(package, version)| (package.to_string(), version.to_string()))
+                .ok_or(eyre!("unexpected extension output: {line:?}"))
+        })
+        .collect()
+}
+
+/// Returns the names of the profiles starting with the default profile. The non-default
+/// profiles are only stored in the editor's global storage file as there is no command for
+/// listing them.
+fn get_profiles(config: &VsCodeConfig) -> Result<Vec<String>> {
+    let default = vec![DEFAULT_PROFILE.to_string()];
+
+    let Some(path) = dirs::config_dir().map(|x| {
+        x.join(config.variant.config_dir_name())
+            .join("User/globalStorage/storage.json")
+    }) else {
+        return Ok(default);
+    };
+
+    if !path.is_file() {
+        return Ok(default);
+    }
+
+    Ok(default
+        .into_iter()
+        .chain(parse_profiles(&std::fs::read_to_string(&path).wrap_err(
+            eyre!("reading vscode storage file: {}", path.display()),
+        )?)?)
+        .collect())
+}
+
+fn parse_profiles(storage: &str) -> Result<Vec<String>> {
+    let storage: Value = serde_json::from_str(storage)?;
+
+    Ok(storage["userDataProfiles"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|x| x["name"].as_str())
+        .map(String::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_storage_profiles() {
+        // trimmed `User/globalStorage/storage.json`
+        let storage = r#"{
+            "userDataProfiles": [
+                { "location": "-6b6f8a2c", "name": "work", "icon": "briefcase" },
+                { "location": "75b2d3c1", "name": "Rust" }
+            ],
+            "theme": "vs-dark"
+        }"#;
+
+        assert_eq!(parse_profiles(storage).unwrap(), ["work", "Rust"]);
+        assert!(parse_profiles("{}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn default_profile_has_no_option() {
+        assert_eq!(profile_args(DEFAULT_PROFILE).count(), 0);
+        assert_eq!(
+            profile_args("work").collect::<Vec<_>>(),
+            ["--profile", "work"]
+        );
+        assert_eq!(
+            VsCodePackageOptions::default().profiles(),
+            [DEFAULT_PROFILE]
+        );
+    }
+}
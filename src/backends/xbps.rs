@@ -1,7 +1,10 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::io::ErrorKind::NotFound;
+use std::io::Write;
+use std::path::Path;
 
 use color_eyre::Result;
-use color_eyre::eyre::eyre;
+use color_eyre::eyre::{Context, eyre};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
@@ -21,7 +24,18 @@ pub struct XbpsPackageOptions {}
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct XbpsRepoOptions {}
+pub struct XbpsRepoOptions {
+    pub url: Option<String>,
+}
+
+/// The directory where administrator `xbps.d` configuration files are placed, files in this
+/// directory override files with the same name in [`XBPS_SYSTEM_CONFIG_DIR`].
+const XBPS_CONFIG_DIR: &str = "/etc/xbps.d";
+/// The directory where `xbps.d` configuration files installed by packages are placed, such as
+/// those installed by the `xbps` package itself or the `void-repo-*` packages.
+const XBPS_SYSTEM_CONFIG_DIR: &str = "/usr/share/xbps.d";
+/// The prefix used for the names of the `xbps.d` drop-in files owned by metapac.
+const METAPAC_DROP_IN_PREFIX: &str = "metapac-";
 
 impl Backend for Xbps {
     type Config = XbpsConfig;
@@ -152,31 +166,176 @@ impl Backend for Xbps {
         run_command(["xbps-install", "--sync"], Perms::Sudo)
     }
 
-    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
-        Ok(BTreeMap::new())
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        let stdout =
+            run_command_for_stdout(["xbps-query", "--list-repos"], Perms::Same, StdErr::Show)?;
+
+        // example output:
+        //  9594 https://repo-default.voidlinux.org/current (RSA signed)
+        //    -1 https://repo-default.voidlinux.org/current/nonfree (RSA maybe-signed)
+        let urls = stdout
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1))
+            .collect::<BTreeSet<_>>();
+
+        let config_files = read_repository_lines(Path::new(XBPS_CONFIG_DIR))?;
+        let system_config_files = read_repository_lines(Path::new(XBPS_SYSTEM_CONFIG_DIR))?;
+
+        // repos from package provided files (that haven't been overridden) are managed by adding
+        // or removing the package that provides them, such as `void-repo-nonfree`, so we don't
+        // report them as repos
+        let package_urls = system_config_files
+            .iter()
+            .filter(|(file_name, _)| !config_files.contains_key(*file_name))
+            .flat_map(|(_, urls)| urls.iter().map(String::as_str))
+            .collect::<BTreeSet<_>>();
+
+        let metapac_urls = config_files
+            .iter()
+            .filter_map(|(file_name, urls)| {
+                file_name
+                    .strip_prefix(METAPAC_DROP_IN_PREFIX)
+                    .and_then(|x| x.strip_suffix(".conf"))
+                    .map(|name| (name, urls))
+            })
+            .flat_map(|(name, urls)| urls.iter().map(move |url| (url.as_str(), name)))
+            .collect::<BTreeMap<_, _>>();
+
+        Ok(urls
+            .into_iter()
+            .filter(|url| !package_urls.contains(url))
+            .map(|url| match metapac_urls.get(url) {
+                Some(name) => (
+                    (*name).to_string(),
+                    Self::RepoOptions {
+                        url: Some(url.to_string()),
+                    },
+                ),
+                None => (url.to_string(), Self::RepoOptions { url: None }),
+            })
+            .collect())
     }
 
     fn add_repos(
         repos: &BTreeMap<String, Self::RepoOptions>,
-        _: bool,
+        no_confirm: bool,
         _: &Self::Config,
     ) -> Result<()> {
-        if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+        for (repo, options) in repos {
+            if repo.starts_with("void-repo-") {
+                return Err(eyre!(
+                    "{repo:?} is a package not a repo, official void sub-repos are enabled by adding their \"void-repo-*\" package to your xbps packages instead"
+                ));
+            }
+
+            if !repo
+                .chars()
+                .all(|x| x.is_ascii_alphanumeric() || matches!(x, '.' | '_' | '-'))
+            {
+                return Err(eyre!(
+                    "invalid xbps repo name: {repo:?}, repos added by metapac must have a name made up of only alphanumeric characters, '.', '_' or '-'"
+                ));
+            }
+
+            let url = options
+                .url
+                .as_deref()
+                .ok_or(eyre!("xbps repos must have the \"url\" option set"))?;
+
+            let mut drop_in = tempfile::NamedTempFile::new()?;
+            writeln!(drop_in, "# this file is managed by metapac")?;
+            writeln!(drop_in, "repository={url}")?;
+
+            run_command(
+                [
+                    "install",
+                    "-D",
+                    "-m",
+                    "644",
+                    drop_in
+                        .path()
+                        .to_str()
+                        .ok_or(eyre!("temporary file path should be valid utf-8"))?,
+                    &metapac_drop_in_path(repo),
+                ],
+                Perms::Sudo,
+            )?;
+        }
+
+        if !repos.is_empty() {
+            // sync the new repos' index so that the user can accept their signing keys
+            run_command(
+                ["xbps-install", "--sync"]
+                    .into_iter()
+                    .chain(no_confirm.then_some("--yes")),
+                Perms::Sudo,
+            )?;
         }
+
+        Ok(())
     }
 
     fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
-        if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+        for repo in repos {
+            let drop_in_path = metapac_drop_in_path(repo);
+
+            if !Path::new(&drop_in_path).is_file() {
+                return Err(eyre!(
+                    "the {repo:?} xbps repo was not added by metapac and so cannot be removed by metapac, remove the `repository={repo}` line from the relevant configuration file in {XBPS_CONFIG_DIR:?} manually or if it was added by a \"void-repo-*\" package uninstall that package instead"
+                ));
+            }
+
+            run_command(["rm", drop_in_path.as_str()], Perms::Sudo)?;
         }
+
+        Ok(())
     }
 
     fn version(_: &Self::Config) -> Result<String> {
         run_command_for_stdout(["xbps-query", "--version"], Perms::Same, StdErr::Show)
     }
 }
+
+fn metapac_drop_in_path(repo: &str) -> String {
+    format!("{XBPS_CONFIG_DIR}/{METAPAC_DROP_IN_PREFIX}{repo}.conf")
+}
+
+/// Returns the `repository=` urls from each `.conf` file in the given `xbps.d` directory keyed by
+/// file name.
+fn read_repository_lines(dir: &Path) -> Result<BTreeMap<String, Vec<String>>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(err).wrap_err(eyre!("reading xbps config directory {dir:?}")),
+    };
+
+    let mut files = BTreeMap::new();
+    for entry in entries {
+        let path = entry?.path();
+
+        if path.extension().is_none_or(|x| x != "conf") {
+            continue;
+        }
+
+        let Some(file_name) = path.file_name().and_then(|x| x.to_str()) else {
+            continue;
+        };
+
+        let contents =
+            std::fs::read_to_string(&path).wrap_err(eyre!("reading xbps config file {path:?}"))?;
+
+        let urls = contents
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("repository="))
+            .map(|url| url.trim().to_string())
+            .collect();
+
+        files.insert(file_name.to_string(), urls);
+    }
+
+    Ok(files)
+}
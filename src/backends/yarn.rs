@@ -1,5 +1,10 @@
 use std::collections::{BTreeMap, BTreeSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::backends::node::{
+    ScopeConfigFormat, add_scope_registries, extract_dependencies, installed_version, package_spec,
+    read_scope_registries, remove_scope_registries, scope_registries,
+};
 
 use crate::cmd::{run_command, run_command_for_stdout};
 use crate::prelude::*;
@@ -17,11 +22,15 @@ pub struct YarnConfig {}
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct YarnPackageOptions {}
+pub struct YarnPackageOptions {
+    pub version: Option<String>,
+}
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct YarnRepoOptions {}
+pub struct YarnRepoOptions {
+    pub registry: Option<String>,
+}
 
 impl Backend for Yarn {
     type Config = YarnConfig;
@@ -64,23 +73,15 @@ impl Backend for Yarn {
         }
 
         let value: Value = serde_json::from_str(&std::fs::read_to_string(package_file)?)?;
-        let object = value
-            .as_object()
-            .ok_or(eyre!("package file should be an object"))?;
-
-        if !object.contains_key("dependencies") {
-            return Ok(BTreeMap::new());
-        }
+        let node_modules = Path::new(&dir).join("node_modules");
 
-        let names = object["dependencies"]
-            .as_object()
-            .ok_or(eyre!("the dependencies value should be an object"))?
-            .iter()
-            .map(|(name, _)| name.clone());
-
-        Ok(names
+        // the package file only contains the version ranges so we prefer the installed versions
+        Ok(extract_dependencies(&value)?
             .into_iter()
-            .map(|name| (name, YarnPackageOptions {}))
+            .map(|(name, version)| {
+                let version = installed_version(&node_modules, &name).or(version);
+                (name, YarnPackageOptions { version })
+            })
             .collect())
     }
 
@@ -93,7 +94,12 @@ impl Backend for Yarn {
             run_command(
                 ["yarn", "global", "add"]
                     .into_iter()
-                    .chain(packages.keys().map(String::as_str)),
+                    .map(String::from)
+                    .chain(
+                        packages.iter().map(|(package, options)| {
+                            package_spec(package, options.version.as_ref())
+                        }),
+                    ),
                 Perms::Same,
             )?;
         }
@@ -137,8 +143,24 @@ impl Backend for Yarn {
         })
     }
 
-    fn get_installed_repos(_: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
-        Ok(BTreeMap::new())
+    fn get_installed_repos(config: &Self::Config) -> Result<BTreeMap<String, Self::RepoOptions>> {
+        if Self::version(config).is_err() {
+            return Ok(BTreeMap::new());
+        }
+
+        Ok(
+            read_scope_registries(&scope_config_path()?, ScopeConfigFormat::Yarnrc)?
+                .into_iter()
+                .map(|(scope, registry)| {
+                    (
+                        scope,
+                        Self::RepoOptions {
+                            registry: Some(registry),
+                        },
+                    )
+                })
+                .collect(),
+        )
     }
 
     fn add_repos(
@@ -147,21 +169,36 @@ impl Backend for Yarn {
         _: &Self::Config,
     ) -> Result<()> {
         if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+            return Ok(());
         }
+
+        add_scope_registries(
+            &scope_config_path()?,
+            ScopeConfigFormat::Yarnrc,
+            &scope_registries(
+                repos
+                    .iter()
+                    .map(|(scope, options)| (scope, &options.registry)),
+            )?,
+        )
     }
 
     fn remove_repos(repos: &BTreeSet<String>, _: bool, _: &Self::Config) -> Result<()> {
         if repos.is_empty() {
-            Ok(())
-        } else {
-            Err(eyre!("unimplemented"))
+            return Ok(());
         }
+
+        remove_scope_registries(&scope_config_path()?, ScopeConfigFormat::Yarnrc, repos)
     }
 
     fn version(_: &Self::Config) -> Result<String> {
         run_command_for_stdout(["yarn", "--version"], Perms::Same, StdErr::Show)
     }
 }
+
+/// Returns the path of the user-level `.yarnrc` file.
+fn scope_config_path() -> Result<PathBuf> {
+    home::home_dir()
+        .map(|x| x.join(".yarnrc"))
+        .ok_or(eyre!("getting the home directory"))
+}
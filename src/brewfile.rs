@@ -0,0 +1,305 @@
+//! Conversion between `brew bundle` Brewfiles and metapac group files.
+//!
+//! Brewfiles are ruby, so rather than evaluating them we only parse the common single line
+//! `tap`, `brew`, `cask`, `mas` and `vscode` entries, any other lines are skipped with a warning.
+
+use color_eyre::Result;
+use regex::Regex;
+
+use crate::backends::brew::{BrewRepoOptions, CASK_PREFIX};
+use crate::prelude::*;
+
+pub fn import(contents: &str) -> Result<AllRawComplexBackendItems> {
+    let quoted = Regex::new(r#""([^"]*)"|'([^']*)'"#)?;
+    let array_args = Regex::new(r"args:\s*\[([^\]]*)\]")?;
+    let hash_args = Regex::new(r"args:\s*\{([^}]*)\}")?;
+    let hash_arg = Regex::new(r#"(\w+):\s*(?:"([^"]*)"|'([^']*)'|(\w+))"#)?;
+    let mas_id = Regex::new(r"id:\s*(\d+)")?;
+
+    let strings = |x: &str| -> Vec<String> {
+        quoted
+            .captures_iter(x)
+            .filter_map(|x| x.get(1).or_else(|| x.get(2)))
+            .map(|x| x.as_str().to_string())
+            .collect()
+    };
+
+    let mut items = AllRawComplexBackendItems::default();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (kind, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+        // lines such as `end`, `if OS.mac?` or `cask_args require_sha: true` aren't entries
+        let name = strings(rest).into_iter().next();
+        let Some(name) =
+            name.filter(|_| matches!(kind, "tap" | "brew" | "cask" | "mas" | "vscode"))
+        else {
+            log::warn!(
+                "skipping unsupported line {} of the Brewfile: {line:?}",
+                index + 1
+            );
+            continue;
+        };
+
+        match kind {
+            "tap" => items
+                .brew
+                .repos
+                .push(complex_item(name, BrewRepoOptions {})),
+            "brew" => {
+                let args = array_args
+                    .captures(rest)
+                    .map(|x| strings(&x[1]))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|x| format!("--{}", x.trim_start_matches('-')))
+                    .collect();
+
+                let name = tap_name(&name, &mut items);
+
+                items
+                    .brew
+                    .packages
+                    .push(complex_item(name, BrewPackageOptions { args }));
+            }
+            "cask" => {
+                let args = hash_args
+                    .captures(rest)
+                    .map(|x| {
+                        hash_arg
+                            .captures_iter(&x[1])
+                            .map(|x| {
+                                // brewfile keys are ruby symbols whereas brew uses kebab-case
+                                let key = x[1].replace('_', "-");
+                                match x.get(2).or_else(|| x.get(3)).or_else(|| x.get(4)) {
+                                    Some(value) if value.as_str() != "true" => {
+                                        format!("--{key}={}", value.as_str())
+                                    }
+                                    _ => format!("--{key}"),
+                                }
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let name = tap_name(&name, &mut items);
+
+                items.brew.packages.push(complex_item(
+                    format!("{CASK_PREFIX}{name}"),
+                    BrewPackageOptions { args },
+                ));
+            }
+            "mas" => match mas_id.captures(rest) {
+                Some(id) => items
+                    .mas
+                    .packages
+                    .push(complex_item(id[1].to_string(), MasPackageOptions {})),
+                None => log::warn!(
+                    "skipping mas app {name:?} without an id on line {} of the Brewfile",
+                    index + 1
+                ),
+            },
+            "vscode" => items
+                .vscode
+                .packages
+                .push(complex_item(name, VsCodePackageOptions::default())),
+            _ => unreachable!("only supported lines are matched"),
+        }
+    }
+
+    Ok(items)
+}
+
+pub fn export(items: &AllComplexBackendItems) -> String {
+    let quote = |x: &str| format!("\"{x}\"");
+
+    let mut lines = Vec::new();
+
+    for repo in items.brew.repos.keys() {
+        lines.push(format!("tap {}", quote(repo)));
+    }
+
+    for (package, item) in &items.brew.packages {
+        if let Some(cask) = package.strip_prefix(CASK_PREFIX) {
+            let args = item
+                .options
+                .args
+                .iter()
+                .map(|x| {
+                    let x = x.trim_start_matches('-');
+                    match x.split_once('=') {
+                        Some((key, value)) => {
+                            format!("{}: {}", key.replace('-', "_"), quote(value))
+                        }
+                        None => format!("{}: true", x.replace('-', "_")),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            if args.is_empty() {
+                lines.push(format!("cask {}", quote(cask)));
+            } else {
+                lines.push(format!(
+                    "cask {}, args: {{ {} }}",
+                    quote(cask),
+                    args.join(", ")
+                ));
+            }
+        } else {
+            let args = item
+                .options
+                .args
+                .iter()
+                .map(|x| quote(x.trim_start_matches('-')))
+                .collect::<Vec<_>>();
+
+            if args.is_empty() {
+                lines.push(format!("brew {}", quote(package)));
+            } else {
+                lines.push(format!(
+                    "brew {}, args: [{}]",
+                    quote(package),
+                    args.join(", ")
+                ));
+            }
+        }
+    }
+
+    // metapac only knows the ids of mas apps so we use them as the names too
+    for package in items.mas.packages.keys() {
+        lines.push(format!("mas {}, id: {package}", quote(package)));
+    }
+
+    for package in items.vscode.packages.keys() {
+        lines.push(format!("vscode {}", quote(package)));
+    }
+
+    lines.into_iter().map(|x| x + "\n").collect()
+}
+
+/// Brewfiles can refer to packages from taps by their tap qualified name such as
+/// `user/tap/package`, whereas brew lists installed packages by their short name. So we return the
+/// short name and make sure the tap is imported as a repo instead.
+fn tap_name(name: &str, items: &mut AllRawComplexBackendItems) -> String {
+    match name.rsplit_once('/') {
+        Some((tap, name)) => {
+            if !items.brew.repos.iter().any(|x| x.name == tap) {
+                items
+                    .brew
+                    .repos
+                    .push(complex_item(tap.to_string(), BrewRepoOptions {}));
+            }
+
+            name.to_string()
+        }
+        None => name.to_string(),
+    }
+}
+
+fn complex_item<T>(name: String, options: T) -> ComplexItem<T> {
+    ComplexItem {
+        name,
+        options,
+        hooks: Hooks::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BREWFILE: &str = indoc::indoc! {r#"
+        # a comment
+        tap "homebrew/bundle"
+        cask_args appdir: "/Applications", require_sha: true
+        brew "neovim", args: ["HEAD"]
+        brew "user/tap/foo"
+        if OS.mac?
+          cask "firefox", args: { appdir: "~/Applications", no_quarantine: true }
+        end
+        mas "Xcode", id: 497799835
+        vscode "rust-lang.rust-analyzer"
+    "#};
+
+    fn to_complex(items: AllRawComplexBackendItems) -> AllComplexBackendItems {
+        AllComplexBackendItems {
+            brew: items.brew.to_non_raw(),
+            mas: items.mas.to_non_raw(),
+            vscode: items.vscode.to_non_raw(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn import_skips_unsupported_lines() {
+        let items = import(BREWFILE).unwrap();
+
+        assert_eq!(
+            items
+                .brew
+                .repos
+                .iter()
+                .map(|x| x.name.as_str())
+                .collect::<Vec<_>>(),
+            ["homebrew/bundle", "user/tap"]
+        );
+        assert_eq!(
+            items
+                .brew
+                .packages
+                .iter()
+                .map(|x| (x.name.as_str(), x.options.clone()))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "neovim",
+                    BrewPackageOptions {
+                        args: vec!["--HEAD".to_string()],
+                    }
+                ),
+                ("foo", BrewPackageOptions::default()),
+                (
+                    "cask:firefox",
+                    BrewPackageOptions {
+                        args: vec![
+                            "--appdir=~/Applications".to_string(),
+                            "--no-quarantine".to_string(),
+                        ],
+                    }
+                ),
+            ]
+        );
+        assert_eq!(items.mas.packages[0].name, "497799835");
+        assert_eq!(items.vscode.packages[0].name, "rust-lang.rust-analyzer");
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let items = to_complex(import(BREWFILE).unwrap());
+
+        let exported = export(&items);
+
+        assert_eq!(
+            exported,
+            indoc::indoc! {r#"
+                tap "homebrew/bundle"
+                tap "user/tap"
+                cask "firefox", args: { appdir: "~/Applications", no_quarantine: true }
+                brew "foo"
+                brew "neovim", args: ["HEAD"]
+                mas "497799835", id: 497799835
+                vscode "rust-lang.rust-analyzer"
+            "#}
+        );
+        let reimported = to_complex(import(&exported).unwrap());
+        assert_eq!(reimported.brew, items.brew);
+        assert_eq!(reimported.mas, items.mas);
+        assert_eq!(reimported.vscode, items.vscode);
+    }
+}
@@ -1,7 +1,7 @@
 //! The clap declarative command line interface
 
 use crate::prelude::*;
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -33,6 +33,8 @@ pub enum MainSubcommand {
     Backends(BackendsCommand),
     CleanCache(CleanCacheCommand),
     Refresh(RefreshCommand),
+    Import(ImportCommand),
+    Export(ExportCommand),
     Completions(CompletionsCommand),
 }
 
@@ -123,6 +125,31 @@ pub struct RefreshCommand {
     pub backends: Vec<String>,
 }
 
+#[derive(Args)]
+/// convert a package list from another tool into group file format
+///
+/// the output is in valid toml group file format to allow writing
+/// the output to a file
+pub struct ImportCommand {
+    /// the format of the file to import
+    pub format: FileFormat,
+    /// the path of the file to import
+    pub path: PathBuf,
+}
+
+#[derive(Args)]
+/// convert the packages required by your groups into a package list for another tool
+pub struct ExportCommand {
+    /// the format to export to
+    pub format: FileFormat,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum FileFormat {
+    /// a `brew bundle` Brewfile
+    Brewfile,
+}
+
 #[derive(Args)]
 /// generate shell completions
 pub struct CompletionsCommand {
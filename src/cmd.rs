@@ -1,5 +1,5 @@
 use std::{
-    collections::VecDeque,
+    collections::{BTreeMap, VecDeque},
     process::{Command, Stdio},
     time::Instant,
 };
@@ -73,6 +73,20 @@ where
 }
 
 pub fn run_command<I, S>(args: I, perms: Perms) -> Result<()>
+where
+    S: Into<String>,
+    I: IntoIterator<Item = S>,
+{
+    run_command_with_env(args, &BTreeMap::new(), perms)
+}
+
+/// Runs a command with the given environment variables set in addition to the inherited
+/// environment.
+pub fn run_command_with_env<I, S>(
+    args: I,
+    envs: &BTreeMap<String, String>,
+    perms: Perms,
+) -> Result<()>
 where
     S: Into<String>,
     I: IntoIterator<Item = S>,
@@ -96,6 +110,7 @@ where
 
     let status = command
         .args(remaining_args)
+        .envs(envs)
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
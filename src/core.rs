@@ -1,6 +1,6 @@
 use clap::CommandFactory;
 use clap_complete::generate;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
 use std::str::FromStr;
 
@@ -9,6 +9,7 @@ use color_eyre::eyre::{Context, Ok, eyre};
 use dialoguer::Confirm;
 use strum::IntoEnumIterator;
 
+use crate::brewfile;
 use crate::prelude::*;
 
 impl Command {
@@ -46,6 +47,8 @@ impl Command {
             MainSubcommand::Backends(backends) => backends.run(&config),
             MainSubcommand::CleanCache(clean_cache) => clean_cache.run(&hostname, &config),
             MainSubcommand::Refresh(refresh) => refresh.run(&hostname, &config),
+            MainSubcommand::Import(import) => import.run(),
+            MainSubcommand::Export(export) => export.run(&hostname, &group_dir, &config),
             MainSubcommand::Completions(completions) => completions.run(),
         }
     }
@@ -122,6 +125,8 @@ impl SyncCommand {
         let enabled_backends = config.enabled_backends(hostname);
         let required = required(hostname, group_dir, config)?;
         let missing = missing(&required, &enabled_backends, config.backend_configs())?;
+        let option_changes =
+            option_changes(&required, &enabled_backends, config.backend_configs())?;
 
         if missing.is_empty() {
             log::info!("nothing to install as there are no missing packages");
@@ -131,11 +136,24 @@ impl SyncCommand {
             print!("{}", missing.clone().to_raw().to_string_pretty()?);
         }
 
+        for (backend, changes) in &option_changes {
+            println!("{backend} package option changes:");
+            for change in changes {
+                println!("  - {change}");
+            }
+        }
+
+        let prompt = if option_changes.is_empty() {
+            "these repos/packages will be installed, do you want to continue?"
+        } else {
+            "these repos/packages will be installed and package options changed, do you want to continue?"
+        };
+
         if self.no_confirm {
             log::info!("proceeding to install packages without confirmation");
-        } else if !missing.is_empty()
+        } else if (!missing.is_empty() || !option_changes.is_empty())
             && !Confirm::new()
-                .with_prompt("these repos/packages will be installed, do you want to continue?")
+                .with_prompt(prompt)
                 .default(true)
                 .show_default(true)
                 .interact()
@@ -172,6 +190,7 @@ impl SyncCommand {
                         for options in missing.$lower_backend.packages.values() {
                             options.hooks.run_after_install()?;
                         }
+                        $upper_backend::sync_package_options(&required.clone().to_non_complex().$lower_backend.packages, self.no_confirm, &config.backend_configs().$lower_backend)?;
                         for options in required.$lower_backend.packages.values() {
                             options.hooks.run_after_sync()?;
                         }
@@ -251,6 +270,33 @@ impl RefreshCommand {
     }
 }
 
+impl ImportCommand {
+    fn run(self) -> Result<()> {
+        let contents = std::fs::read_to_string(&self.path)
+            .wrap_err(eyre!("reading file: {}", self.path.display()))?;
+
+        let items = match self.format {
+            FileFormat::Brewfile => brewfile::import(&contents)?,
+        };
+
+        print!("{}", items.to_string_pretty()?);
+
+        Ok(())
+    }
+}
+
+impl ExportCommand {
+    fn run(self, hostname: &str, group_dir: &Path, config: &Config) -> Result<()> {
+        let required = required(hostname, group_dir, config)?;
+
+        match self.format {
+            FileFormat::Brewfile => print!("{}", brewfile::export(&required)),
+        }
+
+        Ok(())
+    }
+}
+
 impl CompletionsCommand {
     pub fn run(self) -> Result<()> {
         generate(
@@ -287,7 +333,7 @@ fn required(hostname: &str, group_dir: &Path, config: &Config) -> Result<AllComp
     macro_rules! x {
         ($(($upper_backend:ident, $lower_backend:ident)),*) => {
             $(
-                let are_valid_packages = $upper_backend::are_packages_valid(&required.clone().$lower_backend.packages.keys().cloned().collect(), &config.backend_configs().$lower_backend);
+                let are_valid_packages = $upper_backend::are_packages_valid(&required.clone().to_non_complex().$lower_backend.packages, &config.backend_configs().$lower_backend);
 
                 let invalid_packages = are_valid_packages
                     .iter()
@@ -385,6 +431,33 @@ fn missing(
 
     Ok(missing)
 }
+/// Returns the changes each backend would make to the options of the required packages, leaving
+/// out backends without any changes.
+fn option_changes(
+    required: &AllComplexBackendItems,
+    enabled_backends: &BTreeSet<AnyBackend>,
+    backend_configs: &BackendConfigs,
+) -> Result<BTreeMap<AnyBackend, Vec<String>>> {
+    let required = required.clone().to_non_complex();
+
+    let mut option_changes = BTreeMap::new();
+
+    macro_rules! x {
+        ($(($upper_backend:ident, $lower_backend:ident)),*) => {
+            $(
+                if enabled_backends.contains(&AnyBackend::$upper_backend) {
+                    let changes = $upper_backend::package_option_changes(&required.$lower_backend.packages, &backend_configs.$lower_backend)?;
+                    if !changes.is_empty() {
+                        option_changes.insert(AnyBackend::$upper_backend, changes);
+                    }
+                }
+            )*
+        };
+    }
+    apply_backends!(x);
+
+    Ok(option_changes)
+}
 fn package_vec_to_btreeset(vec: Vec<String>) -> BTreeSet<String> {
     let mut packages = BTreeSet::new();
 
@@ -1,7 +1,7 @@
 #![doc = include_str!("../README.md")]
 
-mod backend_ex;
 mod backends;
+mod brewfile;
 mod cli;
 mod cmd;
 mod completions;
@@ -1,22 +1,31 @@
-pub use crate::backend_ex::BackendEx;
 pub use crate::backends::Backend;
 pub use crate::backends::all::{
     AllBackendItems, AllComplexBackendItems, AllRawComplexBackendItems, AnyBackend, BackendConfigs,
 };
+pub use crate::backends::apk::{Apk, ApkPackageOptions};
 pub(crate) use crate::backends::apply_backends;
 pub use crate::backends::apt::{Apt, AptPackageOptions};
 pub use crate::backends::arch::{Arch, ArchConfig, ArchPackageOptions};
 pub use crate::backends::brew::{Brew, BrewPackageOptions};
 pub use crate::backends::bun::{Bun, BunPackageOptions};
 pub use crate::backends::cargo::{Cargo, CargoConfig, CargoPackageOptions};
+pub use crate::backends::conda::{Conda, CondaConfig, CondaPackageOptions};
 pub use crate::backends::dnf::{Dnf, DnfConfig, DnfPackageOptions};
+pub use crate::backends::dotnet::{Dotnet, DotnetPackageOptions};
+pub use crate::backends::emerge::{Emerge, EmergePackageOptions};
 pub use crate::backends::flatpak::{Flatpak, FlatpakConfig, FlatpakPackageOptions};
+pub use crate::backends::gem::{Gem, GemConfig, GemPackageOptions};
+pub use crate::backends::ghextension::{GhExtension, GhExtensionPackageOptions};
+pub use crate::backends::go::{Go, GoPackageOptions};
+pub use crate::backends::guix::{Guix, GuixConfig, GuixPackageOptions};
 pub use crate::backends::mas::{Mas, MasConfig, MasPackageOptions};
 pub use crate::backends::mise::{Mise, MiseConfig, MisePackageOptions};
 pub use crate::backends::nix::{Nix, NixConfig, NixPackageOptions};
 pub use crate::backends::npm::{Npm, NpmPackageOptions};
 pub use crate::backends::pipx::{Pipx, PipxPackageOptions};
 pub use crate::backends::pnpm::{Pnpm, PnpmPackageOptions};
+pub use crate::backends::rpmostree::{RpmOstree, RpmOstreeConfig, RpmOstreePackageOptions};
+pub use crate::backends::rustup::{Rustup, RustupConfig, RustupPackageOptions};
 pub use crate::backends::scoop::{Scoop, ScoopPackageOptions};
 pub use crate::backends::snap::{Snap, SnapPackageOptions};
 pub use crate::backends::uv::{Uv, UvPackageOptions};
@@ -26,8 +35,9 @@ pub use crate::backends::xbps::{Xbps, XbpsPackageOptions};
 pub use crate::backends::yarn::{Yarn, YarnPackageOptions};
 pub use crate::backends::zypper::{Zypper, ZypperPackageOptions};
 pub use crate::cli::{
-    BackendsCommand, CleanCacheCommand, CleanCommand, Command, CompletionsCommand, MainSubcommand,
-    RefreshCommand, SyncCommand, UnmanagedCommand, UpdateAllCommand, UpdateCommand,
+    BackendsCommand, CleanCacheCommand, CleanCommand, Command, CompletionsCommand, ExportCommand,
+    FileFormat, ImportCommand, MainSubcommand, RefreshCommand, SyncCommand, UnmanagedCommand,
+    UpdateAllCommand, UpdateCommand,
 };
 pub use crate::cmd::{Perms, StdErr};
 pub use crate::completions::AnyShell;